@@ -0,0 +1,96 @@
+//! Timed future that can be aborted by a closure based on its running timing.
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Error returned by a [`timed_controllable`](super::TimedFutureExt::timed_controllable) future
+/// if its closure aborted it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aborted {
+    timing: Timing,
+}
+
+impl Aborted {
+    /// Timing of the future up to the end of the poll after which it was aborted.
+    #[must_use]
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+}
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "future aborted after {:?} busy and {:?} idle",
+            self.timing.busy, self.timing.idle
+        )
+    }
+}
+
+impl Error for Aborted {}
+
+pin_project! {
+    /// Future for the [`timed_controllable`](super::TimedFutureExt::timed_controllable) method.
+    pub struct TimedControllable<Fut, F> where Fut: Future, F: FnMut(Timing) -> ControlFlow<()> {
+        recorder: Recorder,
+        op: F,
+        // `None` once aborted, so that the inner future is dropped right away.
+        #[pin]
+        inner: Option<Fut>,
+    }
+}
+
+impl<Fut, F> TimedControllable<Fut, F>
+where
+    Fut: Future,
+    F: FnMut(Timing) -> ControlFlow<()>,
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            op,
+            inner: Some(inner),
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedControllable<Fut, F>
+where
+    Fut: Future,
+    F: FnMut(Timing) -> ControlFlow<()>,
+{
+    type Output = Result<Fut::Output, Aborted>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        let Some(inner) = this.inner.as_mut().as_pin_mut() else {
+            return Poll::Ready(Err(Aborted {
+                timing: this.recorder.timing,
+            }));
+        };
+
+        match this.recorder.poll(inner, cx) {
+            Poll::Ready(output) => Poll::Ready(Ok(output)),
+            Poll::Pending => match (this.op)(this.recorder.timing) {
+                ControlFlow::Continue(()) => Poll::Pending,
+                ControlFlow::Break(()) => {
+                    this.inner.set(None);
+                    Poll::Ready(Err(Aborted {
+                        timing: this.recorder.timing,
+                    }))
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Pluggable accumulation of the timing of completed futures.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::{FixedHistogram, RetryTiming, Timing, TimingHistogram, UtilizationTracker};
+
+/// Statistic fed with the timing of completed futures.
+///
+/// Implement this trait to plug custom statistics into
+/// [`timed_accumulate`](super::TimedFutureExt::timed_accumulate). The accumulators of this crate
+/// implement it as well, so they can be used interchangeably, e.g. behind a
+/// `Mutex<dyn TimingAccumulator + Send>`.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{Timing, TimingAccumulator};
+/// use std::time::Duration;
+///
+/// /// Tracks the slowest future.
+/// #[derive(Default)]
+/// struct Slowest(Duration);
+///
+/// impl TimingAccumulator for Slowest {
+///     fn accumulate(&mut self, timing: Timing) {
+///         self.0 = self.0.max(timing.busy);
+///     }
+/// }
+///
+/// let mut slowest = Slowest::default();
+/// slowest.accumulate(Timing::builder().busy(Duration::from_millis(2)).build());
+/// slowest.accumulate(Timing::builder().busy(Duration::from_millis(1)).build());
+///
+/// assert_eq!(slowest.0, Duration::from_millis(2));
+/// ```
+pub trait TimingAccumulator {
+    /// Add the timing of a completed future.
+    fn accumulate(&mut self, timing: Timing);
+}
+
+/// Sums up busy and idle time.
+impl TimingAccumulator for Timing {
+    fn accumulate(&mut self, timing: Timing) {
+        *self += timing;
+    }
+}
+
+/// Counts the busy time in its bucket.
+impl TimingAccumulator for TimingHistogram {
+    fn accumulate(&mut self, timing: Timing) {
+        self.record_timing(timing);
+    }
+}
+
+/// Counts the busy time in its bucket.
+impl<const N: usize> TimingAccumulator for FixedHistogram<N> {
+    fn accumulate(&mut self, timing: Timing) {
+        self.record_timing(timing);
+    }
+}
+
+/// Records an attempt.
+impl TimingAccumulator for RetryTiming {
+    fn accumulate(&mut self, timing: Timing) {
+        self.record(timing);
+    }
+}
+
+/// Adds the busy time to the current window.
+impl TimingAccumulator for UtilizationTracker {
+    fn accumulate(&mut self, timing: Timing) {
+        self.record(timing);
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_accumulate`](super::TimedFutureExt::timed_accumulate) method.
+    #[derive(Debug)]
+    pub struct TimedAccumulate<Fut, A> where Fut: Future, A: ?Sized {
+        recorder: Recorder,
+        accumulator: Option<Arc<Mutex<A>>>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, A> TimedAccumulate<Fut, A>
+where
+    Fut: Future,
+    A: TimingAccumulator + ?Sized,
+{
+    pub(crate) fn new(inner: Fut, accumulator: Arc<Mutex<A>>) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            accumulator: Some(accumulator),
+            inner,
+        }
+    }
+}
+
+impl<Fut, A> Future for TimedAccumulate<Fut, A>
+where
+    Fut: Future,
+    A: TimingAccumulator + ?Sized,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(accumulator) = this.accumulator.take() {
+                    accumulator
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .accumulate(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
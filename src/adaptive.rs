@@ -0,0 +1,157 @@
+//! Sampled timing with a sampling rate adapting to recent slowness.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Shared state of futures sampled with
+/// [`timed_adaptive`](super::TimedFutureExt::timed_adaptive).
+///
+/// Each future instrumented with `timed_adaptive(base_rate, &state, f)` is sampled if it is the
+/// n-th one created since the last sample, where n is the current sampling interval. Only sampled
+/// futures read the clock and report their timing, all others are polled without overhead. The
+/// interval adapts with every sampled future on completion:
+///
+/// - If its total time, i.e. busy plus idle time, reached the slow threshold, the interval is
+///   halved down to 1, i.e. every future is sampled while futures are slow.
+/// - Otherwise it grows by one up to `base_rate`, i.e. sampling slowly returns to 1 in
+///   `base_rate` while futures are fast.
+///
+/// This multiplicative decrease and additive increase quickly concentrates the instrumentation on
+/// periods of slowness and gradually backs off afterwards. Initially, the interval is `base_rate`.
+///
+/// The state consists of atomics only and is thus [`Sync`] and can be shared by reference
+/// between futures on any number of threads, or put into a `static` since [`new`](Self::new) is
+/// `const`. Updates use relaxed ordering: under contention an update may be lost or a few more or
+/// fewer futures may be sampled than the interval prescribes, which is irrelevant for sampling.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{SamplerState, TimedFutureExt};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// static SAMPLER: SamplerState = SamplerState::new(Duration::from_millis(100));
+///
+/// for _ in 0..100 {
+///     async { 42 }
+///         .timed_adaptive(10, &SAMPLER, |timing| println!("{timing:?}"))
+///         .await;
+/// }
+///
+/// assert_eq!(SAMPLER.interval(10), 10);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SamplerState {
+    slow: Duration,
+    created: AtomicU64,
+    interval: AtomicU32,
+}
+
+impl SamplerState {
+    /// Create a state considering futures taking at least `slow` in total as slow.
+    #[must_use]
+    pub const fn new(slow: Duration) -> Self {
+        Self {
+            slow,
+            created: AtomicU64::new(0),
+            interval: AtomicU32::new(u32::MAX),
+        }
+    }
+
+    /// Current sampling interval for futures instrumented with `base_rate`, i.e. 1 in how many
+    /// futures is sampled.
+    #[must_use]
+    pub fn interval(&self, base_rate: u32) -> u32 {
+        self.interval
+            .load(Ordering::Relaxed)
+            .clamp(1, base_rate.max(1))
+    }
+
+    /// Decide whether the next future is sampled.
+    fn sample(&self, base_rate: u32) -> bool {
+        let created = self.created.fetch_add(1, Ordering::Relaxed);
+        created % u64::from(self.interval(base_rate)) == 0
+    }
+
+    /// Adapt the interval to the timing of a sampled future.
+    fn adapt(&self, base_rate: u32, timing: Timing) {
+        let interval = self.interval(base_rate);
+
+        let interval = if timing.idle.saturating_add(timing.busy) >= self.slow {
+            (interval / 2).max(1)
+        } else {
+            interval.saturating_add(1).min(base_rate.max(1))
+        };
+
+        self.interval.store(interval, Ordering::Relaxed);
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_adaptive`](super::TimedFutureExt::timed_adaptive) method.
+    pub struct TimedAdaptive<'a, Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        base_rate: u32,
+        state: &'a SamplerState,
+        recorder: Recorder,
+        // `None` if this future is not sampled.
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut, F> TimedAdaptive<'a, Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, base_rate: u32, state: &'a SamplerState, op: F) -> Self {
+        let sampled = !cfg!(feature = "disabled") && state.sample(base_rate);
+
+        Self {
+            base_rate,
+            state,
+            recorder: Recorder::new(),
+            op: sampled.then_some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedAdaptive<'_, Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if cfg!(feature = "disabled") || this.op.is_none() {
+            return this.inner.poll(cx);
+        }
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    this.state.adapt(*this.base_rate, this.recorder.timing);
+                    op(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Timed future asserting bounds on its timing on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+
+pin_project! {
+    /// Future for the [`assert_idle_under`](super::TimedFutureExt::assert_idle_under) method.
+    #[derive(Debug)]
+    pub struct AssertIdleUnder<Fut> where Fut: Future {
+        limit: Duration,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> AssertIdleUnder<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, limit: Duration) -> Self {
+        Self {
+            limit,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for AssertIdleUnder<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") || !cfg!(debug_assertions) {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+
+        if result.is_ready() {
+            let idle = this.recorder.timing.idle;
+
+            assert!(
+                idle <= *this.limit,
+                "future was idle for {idle:?} which exceeds the limit of {:?}",
+                this.limit
+            );
+        }
+
+        result
+    }
+}
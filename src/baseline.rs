@@ -0,0 +1,144 @@
+//! Comparing the timing of a future against a recorded baseline to detect regressions.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Expected timing of a future, e.g. recorded on a reference run and checked in CI.
+///
+/// Only the busy time is compared by [`is_regression`](Self::is_regression), since the idle time
+/// mostly depends on the environment. The idle time is kept for reference. With the `serde`
+/// feature, baselines can be saved to and loaded from files in any format supported by serde,
+/// with both times stored as whole nanoseconds in `busy_ns` and `idle_ns`.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{Timing, TimingBaseline};
+/// use std::time::Duration;
+///
+/// let baseline = TimingBaseline::new(Timing::builder().busy(Duration::from_millis(10)).build());
+/// let observed = Timing::builder().busy(Duration::from_millis(12)).build();
+///
+/// assert!(baseline.is_regression(observed, 0.1));
+/// assert!(!baseline.is_regression(observed, 0.25));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimingBaseline {
+    /// Expected busy time.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "busy_ns", with = "crate::units::as_nanos")
+    )]
+    pub busy: Duration,
+    /// Expected idle time.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "idle_ns", with = "crate::units::as_nanos")
+    )]
+    pub idle: Duration,
+}
+
+impl TimingBaseline {
+    /// Create a baseline expecting `timing`, e.g. the timing of a reference run.
+    #[must_use]
+    pub fn new(timing: Timing) -> Self {
+        Self {
+            busy: timing.busy,
+            idle: timing.idle,
+        }
+    }
+
+    /// Return `true` if the busy time of `observed` exceeds the expected busy time by more than
+    /// the relative `tolerance`, e.g. `0.1` for 10%.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tolerance` is negative or not finite.
+    #[must_use]
+    pub fn is_regression(&self, observed: Timing, tolerance: f64) -> bool {
+        assert!(
+            tolerance.is_finite() && tolerance >= 0.0,
+            "tolerance must be finite and non-negative"
+        );
+
+        observed.busy.as_secs_f64() > self.busy.as_secs_f64() * (1.0 + tolerance)
+    }
+}
+
+impl From<Timing> for TimingBaseline {
+    fn from(timing: Timing) -> Self {
+        Self::new(timing)
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_regression_check`](super::TimedFutureExt::timed_regression_check)
+    /// method.
+    #[derive(Debug)]
+    pub struct TimedRegressionCheck<'a, Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        baseline: &'a TimingBaseline,
+        tolerance: f64,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut, F> TimedRegressionCheck<'a, Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, baseline: &'a TimingBaseline, tolerance: f64, op: F) -> Self {
+        assert!(
+            tolerance.is_finite() && tolerance >= 0.0,
+            "tolerance must be finite and non-negative"
+        );
+
+        Self {
+            baseline,
+            tolerance,
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedRegressionCheck<'_, Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let timing = this.recorder.timing;
+                if this.baseline.is_regression(timing, *this.tolerance) {
+                    if let Some(op) = this.op.take() {
+                        op(timing);
+                    }
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
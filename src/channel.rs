@@ -0,0 +1,93 @@
+//! Timed future sending its timing to a channel on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::Sender;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Instrument a future to send its timing to a channel on completion.
+///
+/// Instead of reporting inline with a closure, the [`Timing`] is sent to `tx` so that a single
+/// consumer can aggregate the timing of many futures. If the receiving end has been dropped, the
+/// timing is discarded silently. In general, it is more straightforward to use the
+/// [`super::TimedFutureExt`] extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_channel;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let (tx, rx) = std::sync::mpsc::channel();
+///
+/// let output = timed_channel(async { 42 }, tx).await;
+/// assert_eq!(output, 42);
+///
+/// # if !cfg!(feature = "disabled") {
+/// let timing = rx.recv().unwrap();
+/// assert!(timing.idle.is_zero());
+/// # }
+/// # }
+pub fn timed_channel<Fut>(fut: Fut, tx: Sender<Timing>) -> TimedChannel<Fut>
+where
+    Fut: Future,
+{
+    TimedChannel::new(fut, tx)
+}
+
+pin_project! {
+    /// Future for the [`timed_channel`] function and
+    /// [`timed_channel`](super::TimedFutureExt::timed_channel) method.
+    #[derive(Debug)]
+    pub struct TimedChannel<Fut> where Fut: Future {
+        recorder: Recorder,
+        tx: Option<Sender<Timing>>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedChannel<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, tx: Sender<Timing>) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            tx: Some(tx),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedChannel<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(tx) = this.tx.take() {
+                    // A dropped receiver is not interested in the timing anymore.
+                    let _ = tx.send(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Timed future recording its polls as Chrome trace events.
+//!
+//! Each poll of a [`timed_chrome`](super::TimedFutureExt::timed_chrome) future is recorded as a
+//! complete event (`"ph": "X"`) into a [`ChromeTrace`]. [`ChromeTrace::to_json`] renders the
+//! events in the [Trace Event Format] understood by `chrome://tracing` and [Perfetto], which show
+//! them on a timeline per thread.
+//!
+//! [Trace Event Format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [Perfetto]: https://ui.perfetto.dev
+
+use std::fmt::Write;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+
+/// Small, stable identifier of the current thread for the `tid` field.
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+
+    thread_local! {
+        static ID: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    ID.with(|id| *id)
+}
+
+/// A single poll.
+#[derive(Clone, Debug)]
+struct Event {
+    name: String,
+    start: Instant,
+    end: Instant,
+    tid: u64,
+}
+
+/// Thread-safe buffer of trace events.
+///
+/// Timestamps are relative to the creation of the trace. Requires the `chrome-trace` feature.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{ChromeTrace, TimedFutureExt};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let trace = ChromeTrace::new();
+///
+/// async { 42 }.timed_chrome("answer", &trace).await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// let json = trace.to_json();
+/// assert!(json.contains(r#""name":"answer","ph":"X""#));
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ChromeTrace {
+    start: Instant,
+    events: Mutex<Vec<Event>>,
+}
+
+impl ChromeTrace {
+    /// Create an empty trace starting now.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: clock::now(),
+            events: Mutex::default(),
+        }
+    }
+
+    fn record(&self, event: Event) {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(event);
+    }
+
+    /// Number of recorded events.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Return `true` if no events were recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Render all recorded events as a Trace Event Format JSON object.
+    ///
+    /// Timestamps and durations are in microseconds with nanosecond precision.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        let pid = std::process::id();
+        let mut json = String::from(r#"{"traceEvents":["#);
+
+        for (index, event) in events.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+
+            let ts = event.start.saturating_duration_since(self.start);
+            let dur = event.end.saturating_duration_since(event.start);
+
+            json.push_str(r#"{"name":""#);
+            escape_into(&event.name, &mut json);
+            let _ = write!(
+                json,
+                r#"","ph":"X","ts":{}.{:03},"dur":{}.{:03},"pid":{pid},"tid":{}}}"#,
+                ts.as_micros(),
+                ts.subsec_nanos() % 1000,
+                dur.as_micros(),
+                dur.subsec_nanos() % 1000,
+                event.tid,
+            );
+        }
+
+        json.push_str("]}");
+        json
+    }
+}
+
+impl Default for ChromeTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Append `s` to `json` escaped as the contents of a JSON string.
+fn escape_into(s: &str, json: &mut String) {
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            '\n' => json.push_str(r"\n"),
+            '\r' => json.push_str(r"\r"),
+            '\t' => json.push_str(r"\t"),
+            c if c.is_control() => {
+                let _ = write!(json, r"\u{:04x}", u32::from(c));
+            }
+            c => json.push(c),
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_chrome`](super::TimedFutureExt::timed_chrome) method.
+    #[derive(Debug)]
+    pub struct TimedChrome<'a, Fut> where Fut: Future {
+        name: &'a str,
+        trace: &'a ChromeTrace,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedChrome<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, name: &'a str, trace: &'a ChromeTrace) -> Self {
+        Self { name, trace, inner }
+    }
+}
+
+impl<Fut> Future for TimedChrome<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let start = clock::now();
+        let this = self.project();
+        let result = this.inner.poll(cx);
+        let end = clock::now();
+
+        this.trace.record(Event {
+            name: (*this.name).to_string(),
+            start,
+            end,
+            tid: thread_id(),
+        });
+
+        result
+    }
+}
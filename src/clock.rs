@@ -0,0 +1,264 @@
+//! Clock used to measure polls.
+//!
+//! By default the busy time is the wall-clock time between the start and the end of a poll. With
+//! the `cpu-time` feature enabled on Linux, the busy time is instead measured with the per-thread
+//! CPU clock, so that time the thread was descheduled by the operating system in the middle of a
+//! poll is not counted. On other platforms the feature falls back to wall-clock time.
+//!
+//! With the `quanta` feature, wall-clock time is read from the TSC-based [`quanta`] clock and
+//! mapped onto a [`std::time::Instant`] taken once at the first reading, so that the public API
+//! keeps using standard instants.
+//!
+//! Measurements below the resolution of the clock read as zero, which is detected once by
+//! [`clock_resolution`].
+//!
+//! With the `test-util` feature, a [`MockClock`] installed on a thread replaces both clocks on
+//! that thread, so that tests can assert exact busy and idle times.
+
+#[cfg(feature = "test-util")]
+use std::cell::Cell;
+#[cfg(feature = "test-util")]
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "test-util")]
+thread_local! {
+    static MOCK_NOW: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Current time of the mock clock installed on this thread, if any.
+#[cfg(feature = "test-util")]
+fn mock_now() -> Option<Instant> {
+    MOCK_NOW.with(Cell::get)
+}
+
+/// Current wall-clock time.
+pub(crate) fn now() -> Instant {
+    #[cfg(feature = "test-util")]
+    if let Some(now) = mock_now() {
+        return now;
+    }
+
+    real_now()
+}
+
+#[cfg(not(feature = "quanta"))]
+fn real_now() -> Instant {
+    Instant::now()
+}
+
+#[cfg(feature = "quanta")]
+fn real_now() -> Instant {
+    static BASE: OnceLock<(Instant, quanta::Instant)> = OnceLock::new();
+
+    let (base, quanta_base) = *BASE.get_or_init(|| (Instant::now(), quanta::Instant::now()));
+    base + quanta::Instant::now().duration_since(quanta_base)
+}
+
+/// Smallest non-zero duration the clock measuring busy time can resolve.
+///
+/// Polls shorter than that are measured as either zero or one tick of the clock. On Linux and
+/// macOS the resolution is typically well below a microsecond, but on Windows and some virtualized
+/// or embedded systems it can be as coarse as several milliseconds, so that most polls of fast
+/// futures read as zero busy time and aggregates are skewed. Use
+/// [`timed_above_resolution`](super::TimedFutureExt::timed_above_resolution) to skip reporting
+/// such measurements.
+///
+/// The wall-clock resolution is detected once by reading the clock until it advances, which takes
+/// at most a few ticks on first use. With the `cpu-time` feature on Linux, the resolution of the
+/// thread CPU clock as reported by the operating system is returned instead. A mock clock
+/// installed with the `test-util` feature is not taken into account.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::clock_resolution;
+/// use std::time::Duration;
+///
+/// assert!(clock_resolution() > Duration::ZERO);
+/// ```
+#[must_use]
+pub fn clock_resolution() -> Duration {
+    static RESOLUTION: OnceLock<Duration> = OnceLock::new();
+
+    *RESOLUTION.get_or_init(detect_resolution)
+}
+
+#[cfg(not(all(feature = "cpu-time", target_os = "linux")))]
+fn detect_resolution() -> Duration {
+    const SAMPLES: usize = 16;
+
+    (0..SAMPLES)
+        .map(|_| {
+            let start = real_now();
+            let mut end = real_now();
+            while end == start {
+                end = real_now();
+            }
+            end - start
+        })
+        .min()
+        .unwrap_or(Duration::ZERO)
+}
+
+#[cfg(all(feature = "cpu-time", target_os = "linux"))]
+fn detect_resolution() -> Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // SAFETY: `ts` is a valid, writable timespec and CLOCK_THREAD_CPUTIME_ID is always supported
+    // on Linux.
+    if unsafe { libc::clock_getres(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return Duration::from_nanos(1);
+    }
+
+    let secs = u64::try_from(ts.tv_sec).unwrap_or(0);
+    let nanos = u32::try_from(ts.tv_nsec).unwrap_or(0);
+    Duration::new(secs, nanos).max(Duration::from_nanos(1))
+}
+
+/// Measures a single poll.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Stopwatch {
+    /// Wall-clock time at which the poll started.
+    pub(crate) start: Instant,
+    #[cfg(all(feature = "cpu-time", target_os = "linux"))]
+    cpu_start: Duration,
+}
+
+impl Stopwatch {
+    /// Start measuring a poll.
+    pub(crate) fn start() -> Self {
+        Self {
+            start: now(),
+            #[cfg(all(feature = "cpu-time", target_os = "linux"))]
+            cpu_start: thread_cpu_time(),
+        }
+    }
+
+    /// Stop measuring and return the wall-clock end of the poll and its busy time.
+    pub(crate) fn stop(&self) -> (Instant, Duration) {
+        #[cfg(all(feature = "cpu-time", target_os = "linux"))]
+        let busy = thread_cpu_time().saturating_sub(self.cpu_start);
+        let end = now();
+        #[cfg(not(all(feature = "cpu-time", target_os = "linux")))]
+        let busy = end - self.start;
+        #[cfg(feature = "test-util")]
+        let busy = if mock_now().is_some() {
+            end - self.start
+        } else {
+            busy
+        };
+        (end, busy)
+    }
+}
+
+/// Manually advanced clock for deterministic tests of instrumented futures.
+///
+/// While installed, all time measurements on the installing thread read the mock clock instead of
+/// the real one, including the busy time with the `cpu-time` feature. Time only passes when
+/// [`advance`](Self::advance) is called, so busy and idle times can be asserted exactly. The
+/// instrumented future must be polled on the installing thread, e.g. by a current-thread runtime
+/// or by polling it manually. Dropping the mock clock restores the previously installed clock.
+/// Requires the `test-util` feature.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{MockClock, TimedFutureExt};
+/// use std::pin::pin;
+/// use std::task::Poll;
+/// use std::time::Duration;
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+///
+/// let clock = MockClock::install();
+/// let mut pending = true;
+///
+/// let mut fut = pin!(std::future::poll_fn(|cx| {
+///     // Simulate 2ms of work in each poll
+///     clock.advance(Duration::from_millis(2));
+///
+///     if std::mem::take(&mut pending) {
+///         cx.waker().wake_by_ref();
+///         Poll::Pending
+///     } else {
+///         Poll::Ready(())
+///     }
+/// })
+/// .timed_collect());
+///
+/// assert!(futures::poll!(fut.as_mut()).is_pending());
+/// clock.advance(Duration::from_millis(10));
+///
+/// let Poll::Ready(((), timing)) = futures::poll!(fut) else {
+///     unreachable!();
+/// };
+///
+/// # if !cfg!(feature = "disabled") {
+/// assert_eq!(timing.busy, Duration::from_millis(4));
+/// assert_eq!(timing.idle, Duration::from_millis(10));
+/// # }
+/// # }
+/// ```
+#[cfg(feature = "test-util")]
+#[derive(Debug)]
+pub struct MockClock {
+    previous: Option<Instant>,
+    // The clock is installed per thread and must be dropped on the installing thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+#[cfg(feature = "test-util")]
+impl MockClock {
+    /// Install a mock clock on the current thread, starting at the current real time.
+    #[must_use]
+    pub fn install() -> Self {
+        let previous = MOCK_NOW.with(|mock| mock.replace(Some(real_now())));
+
+        Self {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Advance the mock clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        MOCK_NOW.with(|mock| mock.set(mock.get().map(|now| now + duration)));
+    }
+
+    /// Current time of the mock clock.
+    #[must_use]
+    pub fn now(&self) -> Instant {
+        mock_now().unwrap_or_else(real_now)
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Drop for MockClock {
+    fn drop(&mut self) {
+        MOCK_NOW.with(|mock| mock.set(self.previous));
+    }
+}
+
+/// CPU time consumed by the calling thread.
+#[cfg(all(feature = "cpu-time", target_os = "linux"))]
+fn thread_cpu_time() -> Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // SAFETY: `ts` is a valid, writable timespec and CLOCK_THREAD_CPUTIME_ID is always supported
+    // on Linux.
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return Duration::ZERO;
+    }
+
+    let secs = u64::try_from(ts.tv_sec).unwrap_or(0);
+    let nanos = u32::try_from(ts.tv_nsec).unwrap_or(0);
+    Duration::new(secs, nanos)
+}
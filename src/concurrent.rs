@@ -0,0 +1,212 @@
+//! Combining the busy time of concurrently running futures without double-counting.
+//!
+//! The busy time of a single future is the sum of the durations of its polls. When several
+//! futures are polled concurrently, for example on different worker threads of a multi-threaded
+//! runtime, their polls overlap in wall-clock time. Summing their busy times then counts the
+//! overlapping wall-clock time more than once and the result can even exceed the wall-clock time
+//! the parent took. To get the wall-clock time during which _at least one_ child was busy, the
+//! individual poll intervals have to be recorded and merged, which is what [`timed_intervals`]
+//! and [`ConcurrentTiming`] do.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Wall-clock interval of a single poll.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct BusyInterval {
+    /// Start of the poll.
+    pub start: Instant,
+    /// End of the poll.
+    pub end: Instant,
+}
+
+/// Instrument a future to record its timing and the wall-clock interval of each poll.
+///
+/// On completion the closure receives the [`Timing`] and the intervals in the order the polls
+/// happened. Recording intervals allocates and is only needed to merge the busy time of
+/// concurrently running futures with [`ConcurrentTiming`]. Note that the intervals are always
+/// measured in wall-clock time, even if the `cpu-time` feature is enabled. In general, it is more
+/// straightforward to use the [`super::TimedFutureExt`] extension trait to instrument a future
+/// directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_intervals;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let output = timed_intervals(tokio::task::yield_now(), |_, intervals| {
+///     assert_eq!(intervals.len(), 2);
+/// })
+/// .await;
+/// # }
+pub fn timed_intervals<Fut, F>(fut: Fut, f: F) -> TimedIntervals<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Vec<BusyInterval>),
+{
+    TimedIntervals::new(fut, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_intervals`] function and
+    /// [`timed_intervals`](super::TimedFutureExt::timed_intervals) method.
+    pub struct TimedIntervals<Fut, F> where Fut: Future, F: FnOnce(Timing, Vec<BusyInterval>) {
+        intervals: Vec<BusyInterval>,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedIntervals<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Vec<BusyInterval>),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            intervals: Vec::new(),
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedIntervals<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Vec<BusyInterval>),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+
+        if let Some((start, end)) = this.recorder.last_poll() {
+            this.intervals.push(BusyInterval { start, end });
+        }
+
+        match result {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    op(this.recorder.timing, std::mem::take(this.intervals));
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+/// Collects the poll intervals of concurrently running futures and merges their busy time.
+///
+/// [`ConcurrentTiming::busy`] returns the length of the union of all recorded intervals, i.e.
+/// the wall-clock time during which at least one of the futures was being polled. This is never
+/// larger than [`ConcurrentTiming::summed_busy`], the naive sum over all intervals, and the
+/// difference between both is the amount of overlap. Note that the union says nothing about how
+/// many threads were busy, use the summed busy time for CPU accounting.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{ConcurrentTiming, TimedFutureExt};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let concurrent = ConcurrentTiming::new();
+///
+/// futures::join!(
+///     async { 1 }.timed_intervals(concurrent.recorder()),
+///     async { 2 }.timed_intervals(concurrent.recorder()),
+/// );
+///
+/// assert!(concurrent.busy() <= concurrent.summed_busy());
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ConcurrentTiming {
+    intervals: Mutex<Vec<BusyInterval>>,
+}
+
+impl ConcurrentTiming {
+    /// Create an empty collection.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the poll intervals of one future.
+    pub fn record(&self, intervals: &[BusyInterval]) {
+        self.intervals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend_from_slice(intervals);
+    }
+
+    /// Return a closure recording the intervals of one future, suitable to be passed to
+    /// [`timed_intervals`](super::TimedFutureExt::timed_intervals).
+    pub fn recorder(&self) -> impl FnOnce(Timing, Vec<BusyInterval>) + '_ {
+        move |_, intervals| self.record(&intervals)
+    }
+
+    /// Wall-clock time during which at least one future was busy.
+    #[must_use]
+    pub fn busy(&self) -> Duration {
+        let mut intervals = self
+            .intervals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        intervals.sort_unstable_by_key(|interval| interval.start);
+
+        let mut busy = Duration::ZERO;
+        let mut current: Option<BusyInterval> = None;
+
+        for interval in intervals {
+            match current.as_mut() {
+                Some(current) if interval.start <= current.end => {
+                    current.end = current.end.max(interval.end);
+                }
+                _ => {
+                    if let Some(done) = current.replace(interval) {
+                        busy += done.end - done.start;
+                    }
+                }
+            }
+        }
+
+        if let Some(done) = current {
+            busy += done.end - done.start;
+        }
+
+        busy
+    }
+
+    /// Sum of all recorded intervals, counting overlapping time multiple times.
+    #[must_use]
+    pub fn summed_busy(&self) -> Duration {
+        self.intervals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|interval| interval.end - interval.start)
+            .sum()
+    }
+}
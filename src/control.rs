@@ -0,0 +1,270 @@
+//! Timed future whose busy time accounting can be paused and resumed, and scopes excluding
+//! sub-futures from it.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::clock::Stopwatch;
+use crate::timed::Recorder;
+use crate::Timing;
+
+thread_local! {
+    static CURRENT: RefCell<Option<TimingControl>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Default)]
+struct State {
+    paused_since: Option<Stopwatch>,
+    /// Busy time paused during the current poll before `paused_since`.
+    paused: Duration,
+}
+
+impl State {
+    /// Start accounting the paused time of the poll measured by `stopwatch`.
+    fn start_poll(&mut self, stopwatch: Stopwatch) {
+        self.paused = Duration::ZERO;
+
+        // Restart on the polling thread, which matters for CPU time
+        if let Some(since) = &mut self.paused_since {
+            *since = stopwatch;
+        }
+    }
+
+    /// Busy time paused since the start of the poll.
+    fn paused_in_poll(&self) -> Duration {
+        self.paused.saturating_add(
+            self.paused_since
+                .map_or(Duration::ZERO, |since| since.stop().1),
+        )
+    }
+}
+
+/// Handle to pause and resume the busy time accounting of a
+/// [`timed_controlled`](super::TimedFutureExt::timed_controlled) future.
+///
+/// Busy time spent while paused is not counted, which allows carving out sections such as time
+/// spent in a child future that is accounted for separately. Time between polls is idle time
+/// regardless of the pause state. Pausing an already paused handle or resuming a running one has
+/// no effect. The handle can be cloned and shared across threads.
+///
+/// The paused time is measured with the same clock as the busy time, i.e. as CPU time of the
+/// polling thread with the `cpu-time` feature. Hence, pausing and resuming should happen inside
+/// the instrumented future, where the handle is also available from [`current`](Self::current).
+#[derive(Clone, Debug, Default)]
+pub struct TimingControl {
+    state: Arc<Mutex<State>>,
+}
+
+impl TimingControl {
+    /// Handle of the innermost [`timed_controlled`](super::TimedFutureExt::timed_controlled)
+    /// future currently being polled on this thread.
+    ///
+    /// Returns `None` if called outside of such a future. With the `disabled` feature, it returns
+    /// a new handle whose pausing has no effect, so that callers behave the same in both builds.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        if cfg!(feature = "disabled") {
+            return Some(Self::default());
+        }
+
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Stop counting busy time.
+    pub fn pause(&self) {
+        if cfg!(feature = "disabled") {
+            return;
+        }
+
+        let mut state = self.lock();
+        if state.paused_since.is_none() {
+            state.paused_since = Some(Stopwatch::start());
+        }
+    }
+
+    /// Continue counting busy time.
+    pub fn resume(&self) {
+        let mut state = self.lock();
+        if let Some(since) = state.paused_since.take() {
+            state.paused = state.paused.saturating_add(since.stop().1);
+        }
+    }
+
+    /// Return `true` if busy time is currently not counted.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused_since.is_some()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_controlled`](super::TimedFutureExt::timed_controlled) method.
+    pub struct TimedControlled<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        control: TimingControl,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedControlled<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> (Self, TimingControl) {
+        let control = TimingControl::default();
+
+        let fut = Self {
+            control: control.clone(),
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        };
+
+        (fut, control)
+    }
+}
+
+impl<Fut, F> Future for TimedControlled<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        // Measure paused time only within the poll so that it is never more than the busy time
+        let stopwatch = Stopwatch::start();
+        this.control.lock().start_poll(stopwatch);
+        let previous = CURRENT.with(|current| current.replace(Some(this.control.clone())));
+        let result = this.inner.poll(cx);
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        let paused = this.control.lock().paused_in_poll();
+        this.recorder.record(stopwatch);
+
+        this.recorder.timing.busy = this.recorder.timing.busy.saturating_sub(paused);
+
+        match result {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    op(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+/// Exclude sub-futures from the busy time of the enclosing instrumented future.
+///
+/// Call [`SkipTiming::await_scope`] with a sub-future to pause the busy time accounting of the
+/// innermost [`timed_controlled`](super::TimedFutureExt::timed_controlled) future while the
+/// sub-future is polled. The accounting resumes as soon as each poll of the sub-future returns,
+/// so nothing needs to be undone if the scope is dropped early.
+///
+/// The enclosing future is looked up on every poll via [`TimingControl::current`]. If the scope
+/// does not run inside a `timed_controlled` future, e.g. inside a plain
+/// [`timed`](super::TimedFutureExt::timed) future, it has no effect and the busy time of the
+/// sub-future is counted as usual. Likewise, if the sub-future is moved to and polled on a
+/// different task, its busy time is not subtracted from the original parent. The skipped time is
+/// measured like the busy time it is subtracted from, i.e. as CPU time with the `cpu-time`
+/// feature.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{skip_timing, TimedFutureExt, Timing};
+/// # use std::time::{Duration, Instant};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let (fut, _control) = async {
+///     skip_timing()
+///         .await_scope(async {
+///             let start = Instant::now();
+///             while start.elapsed() < Duration::from_millis(10) {}
+///         })
+///         .await;
+///     42
+/// }
+/// .timed_controlled(|Timing { busy, .. }| {
+///     assert!(busy < Duration::from_millis(10));
+/// });
+///
+/// assert_eq!(fut.await, 42);
+/// # }
+/// ```
+#[must_use]
+pub fn skip_timing() -> SkipTiming {
+    SkipTiming { _private: () }
+}
+
+/// Region created by [`skip_timing`].
+#[derive(Debug)]
+pub struct SkipTiming {
+    _private: (),
+}
+
+impl SkipTiming {
+    /// Run `fut` without counting its busy time towards the enclosing instrumented future.
+    pub fn await_scope<Fut>(self, fut: Fut) -> SkipScope<Fut>
+    where
+        Fut: Future,
+    {
+        SkipScope { inner: fut }
+    }
+}
+
+pin_project! {
+    /// Future for the [`SkipTiming::await_scope`] method.
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct SkipScope<Fut> {
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> Future for SkipScope<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if cfg!(feature = "disabled") {
+            return this.inner.poll(cx);
+        }
+
+        // Leave an explicit pause of the parent untouched
+        let Some(control) = TimingControl::current().filter(|control| !control.is_paused()) else {
+            return this.inner.poll(cx);
+        };
+
+        control.pause();
+        let result = this.inner.poll(cx);
+        control.resume();
+        result
+    }
+}
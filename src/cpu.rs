@@ -0,0 +1,100 @@
+//! Timed future calling a closure if the CPU time of a poll exceeds a given threshold.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::clock::Stopwatch;
+use crate::Threshold;
+
+/// Instrument a future to call a closure if the CPU time of a poll exceeds a certain threshold.
+/// The closure is called for _each_ poll that exceeds the threshold.
+///
+/// Unlike [`warn_if`](super::warn_if), which measures the wall-clock duration of a poll, this
+/// measures the time the polling thread actually ran on a CPU. Polls that took long only because
+/// the operating system descheduled the thread, e.g. on an overloaded machine, or because they
+/// blocked in a system call do not trigger the closure, so it only fires for genuinely CPU-bound
+/// polls. Requires the `cpu-time` feature. The thread CPU clock is only read on Linux, other
+/// platforms fall back to wall-clock time and thus behave like [`warn_if`](super::warn_if).
+///
+/// In general, it is more straightforward to use the [`super::TimedFutureExt`] extension trait to
+/// instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::warn_if_cpu;
+/// use std::time::{Duration, Instant};
+/// # #[tokio::main]
+/// # async fn main() {
+/// let crunching = async {
+///     let start = Instant::now();
+///     while start.elapsed() < Duration::from_millis(10) {}
+/// };
+///
+/// warn_if_cpu(crunching, Duration::from_millis(1), |duration| {
+///     eprintln!("poll used {duration:?} of CPU time");
+/// })
+/// .await;
+/// # }
+pub fn warn_if_cpu<Fut, F>(fut: Fut, threshold: impl Into<Threshold>, op: F) -> WarnIfCpu<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration),
+{
+    WarnIfCpu::new(fut, threshold.into().as_duration(), op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_cpu`] function and
+    /// [`warn_if_cpu`](super::TimedFutureExt::warn_if_cpu) method.
+    pub struct WarnIfCpu<Fut, F> where Fut: Future, F: Fn(Duration) {
+        threshold: Duration,
+        op: F,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> WarnIfCpu<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration),
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, op: F) -> Self {
+        Self {
+            threshold,
+            op,
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for WarnIfCpu<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        // With the `cpu-time` feature, the stopwatch measures busy time with the thread CPU clock
+        let stopwatch = Stopwatch::start();
+        let result = this.inner.poll(cx);
+        let (_, busy) = stopwatch.stop();
+
+        if busy >= *this.threshold {
+            (this.op)(busy);
+        }
+
+        result
+    }
+}
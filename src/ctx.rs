@@ -0,0 +1,92 @@
+//! Timed future passing a context value alongside the timing.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Instrument a future to pass `ctx` together with its timing to a closure on completion.
+///
+/// The context, e.g. a request or trace ID computed at the call site, is moved into the returned
+/// future and handed back to the closure, which then does not need to capture it. In general, it
+/// is more straightforward to use the [`super::TimedFutureExt`] extension trait to instrument a
+/// future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_ctx;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let request_id = 7_u64;
+///
+/// let output = timed_ctx(async { 42 }, request_id, |request_id, timing| {
+///     println!("request {request_id} took {timing:?}");
+/// })
+/// .await;
+///
+/// assert_eq!(output, 42);
+/// # }
+pub fn timed_ctx<Fut, C, F>(fut: Fut, ctx: C, f: F) -> TimedCtx<Fut, C, F>
+where
+    Fut: Future,
+    F: FnOnce(C, Timing),
+{
+    TimedCtx::new(fut, ctx, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_ctx`] function and [`timed_ctx`](super::TimedFutureExt::timed_ctx)
+    /// method.
+    pub struct TimedCtx<Fut, C, F> where Fut: Future, F: FnOnce(C, Timing) {
+        recorder: Recorder,
+        op: Option<(C, F)>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, C, F> TimedCtx<Fut, C, F>
+where
+    Fut: Future,
+    F: FnOnce(C, Timing),
+{
+    pub(crate) fn new(inner: Fut, ctx: C, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            op: Some((ctx, op)),
+            inner,
+        }
+    }
+}
+
+impl<Fut, C, F> Future for TimedCtx<Fut, C, F>
+where
+    Fut: Future,
+    F: FnOnce(C, Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some((ctx, op)) = this.op.take() {
+                    op(ctx, this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
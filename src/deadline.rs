@@ -0,0 +1,99 @@
+//! Timed future reporting the slack left before a deadline on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Instrument a future to record its timing and the slack left before `deadline`.
+///
+/// On completion, the closure receives the [`Timing`] and the time between the completion and the
+/// deadline. If the deadline was missed, the slack is [`Duration::ZERO`]. In general, it is more
+/// straightforward to use the [`super::TimedFutureExt`] extension trait to instrument a future
+/// directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_deadline;
+/// use std::time::{Duration, Instant};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let deadline = Instant::now() + Duration::from_secs(1);
+///
+/// let output = timed_deadline(async { 42 }, deadline, |_, slack| {
+///     assert!(!slack.is_zero());
+/// })
+/// .await;
+///
+/// assert_eq!(output, 42);
+/// # }
+pub fn timed_deadline<Fut, F>(fut: Fut, deadline: Instant, f: F) -> TimedDeadline<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Duration),
+{
+    TimedDeadline::new(fut, deadline, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_deadline`] function and
+    /// [`timed_deadline`](super::TimedFutureExt::timed_deadline) method.
+    pub struct TimedDeadline<Fut, F> where Fut: Future, F: FnOnce(Timing, Duration) {
+        deadline: Instant,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedDeadline<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Duration),
+{
+    pub(crate) fn new(inner: Fut, deadline: Instant, op: F) -> Self {
+        Self {
+            deadline,
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedDeadline<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    let completion = this.recorder.last_poll_end().unwrap_or_else(clock::now);
+                    let slack = this.deadline.saturating_duration_since(completion);
+                    op(this.recorder.timing, slack);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Timed future reporting detailed per-poll statistics on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Extended timing of a future including per-poll statistics.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq)]
+pub struct DetailedTiming {
+    /// Idle time as in [`Timing::idle`].
+    pub idle: Duration,
+    /// Busy time as in [`Timing::busy`].
+    pub busy: Duration,
+    /// Number of polls including the final one.
+    pub polls: u64,
+    /// Busy time of the longest poll.
+    pub max_poll: Duration,
+    /// Busy time of the first poll, which often includes one-off setup cost. Together with
+    /// [`busy`](Self::busy) this separates cold-start cost from the steady state.
+    pub first_poll_busy: Duration,
+    /// Busy time of the final poll, i.e. the one returning [`Poll::Ready`].
+    pub final_poll_busy: Duration,
+    /// Number of [`Poll::Pending`] results followed by a re-poll within the yield threshold of
+    /// [`timed_detailed_yields`](super::TimedFutureExt::timed_detailed_yields), i.e. cooperative
+    /// yields rather than waits. Always zero without a threshold.
+    pub yields: u64,
+    /// Number of [`Poll::Pending`] results followed by a re-poll after more than the yield
+    /// threshold, i.e. genuine waits on I/O, timers or other tasks. Without a threshold every
+    /// pending poll counts as a wait.
+    pub waits: u64,
+    /// Number of polls whose waker would not wake the same task as the waker of the previous
+    /// poll according to [`Waker::will_wake`].
+    ///
+    /// Executors are expected to pass the same waker to every poll of a task, so this is usually
+    /// zero. A high count means the waker is swapped between polls, e.g. by a combinator creating
+    /// a new waker per poll, which forces the inner future to clone it again every time it
+    /// registers interest.
+    pub waker_changes: u64,
+}
+
+impl DetailedTiming {
+    /// The busy and idle time as [`Timing`].
+    #[must_use]
+    pub fn timing(&self) -> Timing {
+        Timing {
+            idle: self.idle,
+            busy: self.busy,
+        }
+    }
+
+    /// Share of the busy time spent in the final poll between 0 and 1.
+    ///
+    /// Futures doing bursty work once all their inputs are available have a ratio close to 1. If
+    /// the busy time is zero, 0 is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::DetailedTiming;
+    /// use std::time::Duration;
+    ///
+    /// let timing = DetailedTiming {
+    ///     busy: Duration::from_millis(4),
+    ///     final_poll_busy: Duration::from_millis(3),
+    ///     ..DetailedTiming::default()
+    /// };
+    ///
+    /// assert_eq!(timing.final_poll_ratio(), 0.75);
+    /// ```
+    #[must_use]
+    pub fn final_poll_ratio(&self) -> f64 {
+        if self.busy.is_zero() {
+            return 0.0;
+        }
+
+        self.final_poll_busy.as_secs_f64() / self.busy.as_secs_f64()
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_detailed`](super::TimedFutureExt::timed_detailed) method.
+    pub struct TimedDetailed<Fut, F> where Fut: Future, F: FnOnce(DetailedTiming) {
+        recorder: Recorder,
+        polls: u64,
+        max_poll: Duration,
+        first_poll_busy: Duration,
+        yield_threshold: Option<Duration>,
+        yields: u64,
+        waits: u64,
+        waker: Option<Waker>,
+        waker_changes: u64,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedDetailed<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(DetailedTiming),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self::with_yield_threshold(inner, None, op)
+    }
+
+    pub(crate) fn with_yield_threshold(
+        inner: Fut,
+        yield_threshold: Option<Duration>,
+        op: F,
+    ) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            polls: 0,
+            max_poll: Duration::ZERO,
+            first_poll_busy: Duration::ZERO,
+            yield_threshold,
+            yields: 0,
+            waits: 0,
+            waker: None,
+            waker_changes: 0,
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedDetailed<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(DetailedTiming),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.waker {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => {
+                *this.waker_changes += 1;
+                waker.clone_from(cx.waker());
+            }
+            None => *this.waker = Some(cx.waker().clone()),
+        }
+
+        let before = this.recorder.timing;
+        let result = this.recorder.poll(this.inner, cx);
+        let poll_busy = this.recorder.timing.busy.saturating_sub(before.busy);
+
+        if *this.polls == 0 {
+            *this.first_poll_busy = poll_busy;
+        } else {
+            // Every poll but the first follows a pending one, classify that by how quickly the
+            // future was polled again
+            let poll_idle = this.recorder.timing.idle.saturating_sub(before.idle);
+
+            match this.yield_threshold {
+                Some(threshold) if poll_idle <= *threshold => *this.yields += 1,
+                _ => *this.waits += 1,
+            }
+        }
+
+        *this.polls += 1;
+        *this.max_poll = (*this.max_poll).max(poll_busy);
+
+        match result {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    op(DetailedTiming {
+                        idle: this.recorder.timing.idle,
+                        busy: this.recorder.timing.busy,
+                        polls: *this.polls,
+                        max_poll: *this.max_poll,
+                        first_poll_busy: *this.first_poll_busy,
+                        final_poll_busy: poll_busy,
+                        yields: *this.yields,
+                        waits: *this.waits,
+                        waker_changes: *this.waker_changes,
+                    });
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Futures calling a closure with the elapsed wall-clock time on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+
+/// Instrument a future to record the wall-clock time from its first poll to its completion.
+///
+/// Unlike [`timed`](super::timed), this does not distinguish busy and idle time and only reads
+/// the clock on the first and the last poll, making it cheaper when only the overall duration is
+/// of interest. Note that the result is wall-clock time and not busy time. In general, it is more
+/// straightforward to use the [`super::TimedFutureExt`] extension trait to instrument a future
+/// directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::elapsed;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let sleep = tokio::time::sleep(Duration::from_millis(1));
+///
+/// elapsed(sleep, |elapsed| {
+///     assert!(elapsed >= Duration::from_millis(1));
+/// })
+/// .await;
+/// # }
+pub fn elapsed<Fut, F>(fut: Fut, f: F) -> Elapsed<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    Elapsed::new(fut, f)
+}
+
+pin_project! {
+    /// Future for the [`elapsed`] function and [`elapsed`](super::TimedFutureExt::elapsed)
+    /// method.
+    pub struct Elapsed<Fut, F> where Fut: Future, F: FnOnce(Duration) {
+        first_poll_start: Option<Instant>,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> Elapsed<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            first_poll_start: None,
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for Elapsed<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let first_poll_start = *this.first_poll_start.get_or_insert_with(clock::now);
+
+        match this.inner.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    op(clock::now() - first_poll_start);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+/// Instrument a future to record the wall-clock time from its construction to its completion.
+///
+/// Unlike [`elapsed`] and the sum of busy and idle time reported by [`timed`](super::timed),
+/// this includes the time before the first poll. For futures that are queued or spawned, that is
+/// the scheduling delay, which matters for end-to-end latency. In general, it is more
+/// straightforward to use the [`super::TimedFutureExt`] extension trait to instrument a future
+/// directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::lifetime;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let fut = lifetime(async { 42 }, |lifetime| {
+///     assert!(lifetime >= Duration::from_millis(1));
+/// });
+///
+/// std::thread::sleep(Duration::from_millis(1));
+/// assert_eq!(fut.await, 42);
+/// # }
+pub fn lifetime<Fut, F>(fut: Fut, f: F) -> Lifetime<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    Lifetime::new(fut, f)
+}
+
+pin_project! {
+    /// Future for the [`lifetime`] function and [`lifetime`](super::TimedFutureExt::lifetime)
+    /// method.
+    pub struct Lifetime<Fut, F> where Fut: Future, F: FnOnce(Duration) {
+        created: Option<Instant>,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> Lifetime<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            created: (!cfg!(feature = "disabled")).then(clock::now),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for Lifetime<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.inner.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some((op, created)) = this.op.take().zip(*this.created) {
+                    op(clock::now() - created);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_lightweight`](super::TimedFutureExt::timed_lightweight) method.
+    pub struct TimedLightweight<Fut, F> where Fut: Future, F: FnOnce(Duration, Duration) {
+        first_poll_start: Option<Instant>,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedLightweight<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration, Duration),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            first_poll_start: None,
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedLightweight<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration, Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let start = clock::now();
+        let first_poll_start = *this.first_poll_start.get_or_insert(start);
+
+        match this.inner.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    let end = clock::now();
+                    op(end - first_poll_start, end - start);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Timed future attaching its timing to errors.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+pin_project! {
+    /// Future for the [`timed_err_context`](super::TimedFutureExt::timed_err_context) method.
+    pub struct TimedErrContext<Fut, F> where Fut: Future {
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedErrContext<Fut, F>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F, T, E, E2> Future for TimedErrContext<Fut, F>
+where
+    Fut: Future<Output = Result<T, E>>,
+    F: FnOnce(E, Timing) -> E2,
+{
+    type Output = Result<T, E2>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let result = if cfg!(feature = "disabled") {
+            this.inner.poll(cx)
+        } else {
+            this.recorder.poll(this.inner, cx)
+        };
+
+        match result {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(output)) => Poll::Ready(Ok(output)),
+            Poll::Ready(Err(err)) => {
+                let op = this
+                    .op
+                    .take()
+                    .expect("`TimedErrContext` polled after completion");
+                Poll::Ready(Err(op(err, this.recorder.timing)))
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Timed futures emitting `tracing` events on completion or spans around each poll.
+//!
+//! tokio-console measures the busy time of a _task_ as the time its span is entered, i.e. the
+//! time spent polling the future passed to `tokio::spawn`, and its idle time as the time in
+//! between. For that root future, the timing recorded by this crate thus matches what
+//! tokio-console shows for the task. For futures awaited inside a task, it shows which part of
+//! the task's busy time was spent in them.
+
+use std::future::Future;
+use std::panic::Location;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Nanos;
+
+/// Emit the completion event of [`LogTiming`] at a level only known at runtime.
+macro_rules! log_at {
+    ($level:expr, $($args:tt)+) => {
+        match $level {
+            tracing::Level::ERROR => tracing::error!($($args)+),
+            tracing::Level::WARN => tracing::warn!($($args)+),
+            tracing::Level::INFO => tracing::info!($($args)+),
+            tracing::Level::DEBUG => tracing::debug!($($args)+),
+            tracing::Level::TRACE => tracing::trace!($($args)+),
+        }
+    };
+}
+
+pin_project! {
+    /// Future for the [`emit_timing_event`](super::TimedFutureExt::emit_timing_event) method.
+    #[derive(Debug)]
+    pub struct EmitTimingEvent<'a, Fut> where Fut: Future {
+        name: &'a str,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> EmitTimingEvent<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, name: &'a str) -> Self {
+        Self {
+            name,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for EmitTimingEvent<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+
+        if result.is_ready() {
+            let timing = this.recorder.timing;
+
+            tracing::info!(
+                target: "future_timed",
+                name = *this.name,
+                busy_ns = Nanos::from(timing.busy).0,
+                idle_ns = Nanos::from(timing.idle).0,
+                time.busy = ?timing.busy,
+                time.idle = ?timing.idle,
+                "future completed"
+            );
+        }
+
+        result
+    }
+}
+
+pin_project! {
+    /// Future for the [`emit_console_event`](super::TimedFutureExt::emit_console_event) method.
+    #[derive(Debug)]
+    pub struct EmitConsoleEvent<'a, Fut> where Fut: Future {
+        name: &'a str,
+        location: &'static Location<'static>,
+        polls: u64,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> EmitConsoleEvent<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, name: &'a str, location: &'static Location<'static>) -> Self {
+        Self {
+            name,
+            location,
+            polls: 0,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for EmitConsoleEvent<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+        *this.polls += 1;
+
+        if result.is_ready() {
+            let timing = this.recorder.timing;
+
+            tracing::info!(
+                target: "future_timed::console",
+                kind = "future",
+                future.name = *this.name,
+                loc.file = this.location.file(),
+                loc.line = this.location.line(),
+                loc.col = this.location.column(),
+                polls = *this.polls,
+                busy = ?timing.busy,
+                idle = ?timing.idle,
+                total = ?(timing.busy.saturating_add(timing.idle)),
+                "future completed"
+            );
+        }
+
+        result
+    }
+}
+
+pin_project! {
+    /// Future for the [`log_timing`](super::TimedFutureExt::log_timing) method.
+    #[derive(Debug)]
+    pub struct LogTiming<'a, Fut> where Fut: Future {
+        level: tracing::Level,
+        name: &'a str,
+        polls: u64,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> LogTiming<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, level: tracing::Level, name: &'a str) -> Self {
+        Self {
+            level,
+            name,
+            polls: 0,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for LogTiming<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+        *this.polls += 1;
+
+        if result.is_ready() {
+            let timing = this.recorder.timing;
+
+            log_at!(
+                *this.level,
+                target: "future_timed",
+                operation = %this.name,
+                busy = ?timing.busy,
+                idle = ?timing.idle,
+                polls = *this.polls,
+            );
+        }
+
+        result
+    }
+}
+
+pin_project! {
+    /// Future for the [`trace_each_poll`](super::TimedFutureExt::trace_each_poll) method.
+    #[derive(Debug)]
+    pub struct TraceEachPoll<'a, Fut> where Fut: Future {
+        name: &'a str,
+        polls: u64,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TraceEachPoll<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, name: &'a str) -> Self {
+        Self {
+            name,
+            polls: 0,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TraceEachPoll<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        *this.polls += 1;
+
+        let span = tracing::debug_span!(
+            target: "future_timed",
+            "poll",
+            operation = %this.name,
+            poll = *this.polls,
+            busy = tracing::field::Empty,
+            idle = tracing::field::Empty,
+            pending = tracing::field::Empty,
+        );
+
+        let before = this.recorder.timing;
+        let result = span.in_scope(|| this.recorder.poll(this.inner, cx));
+        let after = this.recorder.timing;
+
+        span.record(
+            "busy",
+            tracing::field::debug(after.busy.saturating_sub(before.busy)),
+        );
+        span.record(
+            "idle",
+            tracing::field::debug(after.idle.saturating_sub(before.idle)),
+        );
+        span.record("pending", result.is_pending());
+
+        result
+    }
+}
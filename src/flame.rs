@@ -0,0 +1,172 @@
+//! Timed future recording busy time per label stack for flamegraphs.
+//!
+//! Each [`timed_flame`](super::TimedFutureExt::timed_flame) future determines its label stack on
+//! its first poll: instrumented futures first polled from within the poll of another one nest
+//! below it, forming stacks like `request;parse`. While polled, a future publishes its frame in a
+//! thread-local so that nested futures can find it, much like a task-local. Each future sums its
+//! own _self_ busy time, i.e. the busy time not spent polling nested instrumented futures, and
+//! adds it to a global collector under its stack on completion. [`take_flame_folded`] returns the
+//! collected data in the folded stack format understood by tools like `inferno` or
+//! `flamegraph.pl`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::clock::Stopwatch;
+
+thread_local! {
+    static CURRENT: RefCell<Option<Frame>> = const { RefCell::new(None) };
+}
+
+/// Stack of the innermost instrumented future being polled and the busy time of nested polls.
+struct Frame {
+    stack: Arc<str>,
+    children: Duration,
+}
+
+fn collector() -> &'static Mutex<HashMap<Arc<str>, Duration>> {
+    static COLLECTOR: OnceLock<Mutex<HashMap<Arc<str>, Duration>>> = OnceLock::new();
+    COLLECTOR.get_or_init(Mutex::default)
+}
+
+/// Return the busy time collected so far in folded stack format and reset the collector.
+///
+/// Each line consists of the semicolon-separated label stack followed by a space and the self
+/// busy time in microseconds. Lines are sorted by stack. Stacks with less than a microsecond of
+/// busy time are omitted. Futures dropped before completion are not included.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{take_flame_folded, TimedFutureExt};
+/// # use std::time::{Duration, Instant};
+/// # fn spin(duration: Duration) {
+/// #     let start = Instant::now();
+/// #     while start.elapsed() < duration {}
+/// # }
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// async {
+///     async {
+///         spin(Duration::from_millis(1));
+///     }
+///     .timed_flame("parse")
+///     .await;
+/// }
+/// .timed_flame("request")
+/// .await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// let folded = take_flame_folded();
+/// assert!(folded.lines().any(|line| line.starts_with("request;parse ")));
+/// # }
+/// # }
+/// ```
+#[must_use]
+pub fn take_flame_folded() -> String {
+    let collected =
+        std::mem::take(&mut *collector().lock().unwrap_or_else(PoisonError::into_inner));
+    let mut stacks = collected.into_iter().collect::<Vec<_>>();
+    stacks.sort_unstable();
+
+    let mut folded = String::new();
+
+    for (stack, busy) in stacks {
+        let micros = busy.as_micros();
+
+        if micros > 0 {
+            let _ = writeln!(folded, "{stack} {micros}");
+        }
+    }
+
+    folded
+}
+
+pin_project! {
+    /// Future for the [`timed_flame`](super::TimedFutureExt::timed_flame) method.
+    #[derive(Debug)]
+    pub struct TimedFlame<Fut> where Fut: Future {
+        label: &'static str,
+        // Determined on the first poll.
+        stack: Option<Arc<str>>,
+        busy: Duration,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedFlame<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, label: &'static str) -> Self {
+        Self {
+            label,
+            stack: None,
+            busy: Duration::ZERO,
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedFlame<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let label = *this.label;
+
+        let stack = this.stack.get_or_insert_with(|| {
+            CURRENT.with(|current| match &*current.borrow() {
+                Some(parent) => format!("{};{label}", parent.stack).into(),
+                None => label.into(),
+            })
+        });
+
+        let frame = Frame {
+            stack: stack.clone(),
+            children: Duration::ZERO,
+        };
+
+        let previous = CURRENT.with(|current| current.replace(Some(frame)));
+        let stopwatch = Stopwatch::start();
+        let result = this.inner.poll(cx);
+        let (_, busy) = stopwatch.stop();
+        let frame = CURRENT.with(|current| current.replace(previous));
+
+        let children = frame.map_or(Duration::ZERO, |frame| frame.children);
+        *this.busy += busy.saturating_sub(children);
+
+        CURRENT.with(|current| {
+            if let Some(parent) = &mut *current.borrow_mut() {
+                parent.children += busy;
+            }
+        });
+
+        if result.is_ready() {
+            *collector()
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(stack.clone())
+                .or_default() += *this.busy;
+        }
+
+        result
+    }
+}
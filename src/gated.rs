@@ -0,0 +1,101 @@
+//! Timed future separating the wait for a resource from the work done while holding it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Timing of a future gated by the acquisition of a resource.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GatedTiming {
+    /// Timing of acquiring the resource, i.e. the contention.
+    pub wait: Timing,
+    /// Timing of the work done while holding the resource.
+    pub work: Timing,
+}
+
+pin_project! {
+    /// Future for the [`timed_gated`](super::TimedFutureExt::timed_gated) method.
+    pub struct TimedGated<Fut, G, F> where Fut: Future, G: Future, F: FnOnce(GatedTiming) {
+        wait: Recorder,
+        work: Recorder,
+        // Output of the gate, held until the work completes.
+        guard: Option<G::Output>,
+        op: Option<F>,
+        #[pin]
+        gate: G,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, G, F> TimedGated<Fut, G, F>
+where
+    Fut: Future,
+    G: Future,
+    F: FnOnce(GatedTiming),
+{
+    pub(crate) fn new(inner: Fut, gate: G, op: F) -> Self {
+        Self {
+            wait: Recorder::new(),
+            work: Recorder::new(),
+            guard: None,
+            op: Some(op),
+            gate,
+            inner,
+        }
+    }
+}
+
+impl<Fut, G, F> Future for TimedGated<Fut, G, F>
+where
+    Fut: Future,
+    G: Future,
+    F: FnOnce(GatedTiming),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if this.guard.is_none() {
+            let gate = if cfg!(feature = "disabled") {
+                this.gate.poll(cx)
+            } else {
+                this.wait.poll(this.gate, cx)
+            };
+
+            match gate {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(guard) => *this.guard = Some(guard),
+            }
+        }
+
+        if cfg!(feature = "disabled") {
+            let result = this.inner.poll(cx);
+            if result.is_ready() {
+                this.guard.take();
+            }
+            return result;
+        }
+
+        match this.work.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                // Release the resource before reporting
+                this.guard.take();
+                if let Some(op) = this.op.take() {
+                    op(GatedTiming {
+                        wait: this.wait.timing,
+                        work: this.work.timing,
+                    });
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Process-wide default reporter.
+//!
+//! [`set_global_reporter`] installs a reporter once for the whole process. Futures instrumented
+//! with [`timed_default`](super::TimedFutureExt::timed_default) pass their timing to it on
+//! completion. Until a reporter is installed, their timing is discarded.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::Timing;
+
+type Reporter = Box<dyn Fn(Timing) + Send + Sync>;
+
+static REPORTER: OnceLock<Reporter> = OnceLock::new();
+
+/// Error returned by [`set_global_reporter`] if a reporter was already set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetGlobalReporterError(());
+
+impl fmt::Display for SetGlobalReporterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("global reporter already set")
+    }
+}
+
+impl Error for SetGlobalReporterError {}
+
+/// Install the process-wide reporter used by
+/// [`timed_default`](super::TimedFutureExt::timed_default).
+///
+/// The reporter can be set only once; subsequent calls fail and leave the installed reporter in
+/// place. It is called concurrently from whichever threads complete instrumented futures, hence it
+/// must be `Send + Sync` and should not block. Setting it early in `main`, before any instrumented
+/// future completes, ensures that no timing is lost.
+///
+/// Since the reporter is global, all tests of a test binary share it. Tests that depend on it
+/// should live in their own integration test file and install it exactly once, e.g. from a
+/// [`std::sync::Once`].
+///
+/// # Errors
+///
+/// Returns [`SetGlobalReporterError`] if a reporter was already set.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{set_global_reporter, TimedFutureExt};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// set_global_reporter(|timing| println!("{timing:?}")).unwrap();
+///
+/// let output = async { 42 }.timed_default().await;
+/// assert_eq!(output, 42);
+///
+/// assert!(set_global_reporter(|_| {}).is_err());
+/// # }
+pub fn set_global_reporter<F>(reporter: F) -> Result<(), SetGlobalReporterError>
+where
+    F: Fn(Timing) + Send + Sync + 'static,
+{
+    REPORTER
+        .set(Box::new(reporter))
+        .map_err(|_| SetGlobalReporterError(()))
+}
+
+/// Pass `timing` to the global reporter if one is set.
+pub(crate) fn report(timing: Timing) {
+    if let Some(reporter) = REPORTER.get() {
+        reporter(timing);
+    }
+}
@@ -0,0 +1,353 @@
+//! Histograms of busy times and idle gaps with user-defined bucket boundaries.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Histogram counting durations into fixed, user-defined buckets.
+///
+/// Each bucket is identified by its inclusive upper boundary and counts the durations that are
+/// greater than the previous boundary and less than or equal to its own. A final bucket with
+/// boundary [`Duration::MAX`] catches everything above the largest configured boundary. Counts
+/// are per bucket and not cumulative, if you export to a system expecting cumulative buckets such
+/// as Prometheus, sum them up in order.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{TimedFutureExt, TimingHistogram};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let mut histogram = TimingHistogram::with_buckets(&[
+///     Duration::from_millis(1),
+///     Duration::from_millis(5),
+///     Duration::from_millis(10),
+/// ]);
+///
+/// async { 42 }
+///     .timed(|timing| histogram.record_timing(timing))
+///     .await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// assert_eq!(histogram.buckets()[0], (Duration::from_millis(1), 1));
+/// # }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimingHistogram {
+    buckets: Vec<(Duration, u64)>,
+}
+
+impl TimingHistogram {
+    /// Create a histogram with the given upper bucket boundaries.
+    ///
+    /// The boundaries are sorted and deduplicated, a final [`Duration::MAX`] bucket is always
+    /// added.
+    #[must_use]
+    pub fn with_buckets(boundaries: &[Duration]) -> Self {
+        let mut boundaries = boundaries.to_vec();
+        boundaries.push(Duration::MAX);
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        Self {
+            buckets: boundaries.into_iter().map(|b| (b, 0)).collect(),
+        }
+    }
+
+    /// Count `duration` in its bucket.
+    pub fn record(&mut self, duration: Duration) {
+        let index = self.buckets.partition_point(|(b, _)| *b < duration);
+        self.buckets[index].1 += 1;
+    }
+
+    /// Count the busy time of `timing` in its bucket.
+    pub fn record_timing(&mut self, timing: Timing) {
+        self.record(timing.busy);
+    }
+
+    /// Upper bucket boundaries and their counts in ascending order.
+    #[must_use]
+    pub fn buckets(&self) -> &[(Duration, u64)] {
+        &self.buckets
+    }
+
+    /// Total number of recorded durations.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Upper boundary of the bucket containing the `quantile`, e.g. 0.99 for the 99th percentile.
+    ///
+    /// The result is an upper bound of the actual percentile whose precision depends on the
+    /// bucket boundaries. If the percentile falls into the final bucket, [`Duration::MAX`] is
+    /// returned. Returns `None` if nothing was recorded. `quantile` is clamped to `0.0..=1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimingHistogram;
+    /// use std::time::Duration;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let mut histogram = TimingHistogram::with_buckets(&[ms(1), ms(10), ms(100)]);
+    ///
+    /// for _ in 0..99 {
+    ///     histogram.record(ms(1));
+    /// }
+    /// histogram.record(ms(50));
+    ///
+    /// assert_eq!(histogram.percentile(0.5), Some(ms(1)));
+    /// assert_eq!(histogram.percentile(0.99), Some(ms(1)));
+    /// assert_eq!(histogram.percentile(1.0), Some(ms(100)));
+    /// ```
+    #[must_use]
+    // Counts beyond 2^53 lose precision as `f64`, which is irrelevant for a percentile.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percentile(&self, quantile: f64) -> Option<Duration> {
+        let count = self.count();
+
+        if count == 0 {
+            return None;
+        }
+
+        let rank = quantile.clamp(0.0, 1.0) * count as f64;
+        let mut cumulative = 0;
+
+        self.buckets
+            .iter()
+            .find(|(_, bucket_count)| {
+                cumulative += bucket_count;
+                cumulative > 0 && cumulative as f64 >= rank
+            })
+            .map(|(boundary, _)| *boundary)
+    }
+}
+
+/// Histogram with a compile-time number of buckets that never allocates.
+///
+/// Like [`TimingHistogram`], each bucket counts the durations greater than the previous boundary
+/// and less than or equal to its own, durations above the largest boundary are counted as
+/// [`overflow`](Self::overflow). Since the counts are stored inline, the histogram can live in a
+/// `static` or on the stack of memory-constrained targets.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{FixedHistogram, TimedFutureExt};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let mut histogram = FixedHistogram::new([
+///     Duration::from_micros(100),
+///     Duration::from_millis(1),
+/// ]);
+///
+/// async { 42 }.timed_fixed_hist(&mut histogram).await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// assert_eq!(histogram.counts(), &[1, 0]);
+/// assert_eq!(histogram.overflow(), 0);
+/// # }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FixedHistogram<const N: usize> {
+    boundaries: [Duration; N],
+    counts: [u64; N],
+    overflow: u64,
+}
+
+impl<const N: usize> FixedHistogram<N> {
+    /// Create a histogram with the given upper bucket boundaries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `boundaries` are not strictly ascending. When called in a const context, this
+    /// is a compile-time error.
+    #[must_use]
+    pub const fn new(boundaries: [Duration; N]) -> Self {
+        let mut i = 1;
+
+        while i < N {
+            assert!(
+                boundaries[i - 1].as_nanos() < boundaries[i].as_nanos(),
+                "bucket boundaries must be strictly ascending"
+            );
+            i += 1;
+        }
+
+        Self {
+            boundaries,
+            counts: [0; N],
+            overflow: 0,
+        }
+    }
+
+    /// Count `duration` in its bucket.
+    pub fn record(&mut self, duration: Duration) {
+        self.as_mut().record(duration);
+    }
+
+    fn as_mut(&mut self) -> FixedHistogramMut<'_> {
+        FixedHistogramMut {
+            boundaries: &self.boundaries,
+            counts: &mut self.counts,
+            overflow: &mut self.overflow,
+        }
+    }
+
+    /// Count the busy time of `timing` in its bucket.
+    pub fn record_timing(&mut self, timing: Timing) {
+        self.record(timing.busy);
+    }
+
+    /// Upper bucket boundaries in ascending order.
+    #[must_use]
+    pub fn boundaries(&self) -> &[Duration; N] {
+        &self.boundaries
+    }
+
+    /// Counts of the buckets in the order of their boundaries.
+    #[must_use]
+    pub fn counts(&self) -> &[u64; N] {
+        &self.counts
+    }
+
+    /// Number of recorded durations greater than the largest boundary.
+    #[must_use]
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    /// Total number of recorded durations.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum::<u64>() + self.overflow
+    }
+}
+
+/// Mutable view of a [`FixedHistogram`] with the bucket count erased.
+#[derive(Debug)]
+struct FixedHistogramMut<'a> {
+    boundaries: &'a [Duration],
+    counts: &'a mut [u64],
+    overflow: &'a mut u64,
+}
+
+impl FixedHistogramMut<'_> {
+    fn record(self, duration: Duration) {
+        let index = self.boundaries.partition_point(|b| *b < duration);
+
+        match self.counts.get_mut(index) {
+            Some(count) => *count += 1,
+            None => *self.overflow += 1,
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_fixed_hist`](super::TimedFutureExt::timed_fixed_hist) method.
+    #[derive(Debug)]
+    pub struct TimedFixedHist<'a, Fut> where Fut: Future {
+        recorder: Recorder,
+        histogram: Option<FixedHistogramMut<'a>>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedFixedHist<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new<const N: usize>(inner: Fut, histogram: &'a mut FixedHistogram<N>) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            histogram: Some(histogram.as_mut()),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedFixedHist<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(histogram) = this.histogram.take() {
+                    histogram.record(this.recorder.timing.busy);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_idle_hist`](super::TimedFutureExt::timed_idle_hist) method.
+    #[derive(Debug)]
+    pub struct TimedIdleHist<'a, Fut> where Fut: Future {
+        recorder: Recorder,
+        histogram: &'a mut TimingHistogram,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedIdleHist<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, histogram: &'a mut TimingHistogram) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            histogram,
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedIdleHist<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let previous_end = this.recorder.last_poll_end();
+        let result = this.recorder.poll(this.inner, cx);
+
+        if let Some(((start, _), previous_end)) = this.recorder.last_poll().zip(previous_end) {
+            this.histogram.record(start - previous_end);
+        }
+
+        result
+    }
+}
@@ -0,0 +1,98 @@
+//! Timed future calling a closure on completion only if it was mostly idle.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Instrument a future to report its timing only if it was idle for more than `ratio` times its
+/// busy time.
+///
+/// The closure is called once on completion if `idle > ratio * busy`. This surfaces futures that
+/// mostly wait, e.g. on I/O, as opposed to futures that are bound by the CPU. Those may be
+/// candidates for batching or different scheduling. In general, it is more straightforward to use
+/// the [`super::TimedFutureExt`] extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_if_idle_bound;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let sleep = tokio::time::sleep(Duration::from_millis(1));
+///
+/// timed_if_idle_bound(sleep, 10.0, |timing| {
+///     assert!(timing.idle > timing.busy * 10);
+/// })
+/// .await;
+/// # }
+pub fn timed_if_idle_bound<Fut, F>(fut: Fut, ratio: f64, f: F) -> TimedIfIdleBound<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    TimedIfIdleBound::new(fut, ratio, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_if_idle_bound`] function and
+    /// [`timed_if_idle_bound`](super::TimedFutureExt::timed_if_idle_bound) method.
+    pub struct TimedIfIdleBound<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        ratio: f64,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedIfIdleBound<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, ratio: f64, op: F) -> Self {
+        Self {
+            ratio,
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedIfIdleBound<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let timing = this.recorder.timing;
+
+                if timing.idle.as_secs_f64() > timing.busy.as_secs_f64() * *this.ratio {
+                    if let Some(op) = this.op.take() {
+                        op(timing);
+                    }
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
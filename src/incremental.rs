@@ -0,0 +1,62 @@
+//! Timed future reporting each polling session of a restartable future separately.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+pin_project! {
+    /// Future for the [`on_complete_incremental`](super::TimedFutureExt::on_complete_incremental)
+    /// method.
+    pub struct TimedIncremental<Fut, F> where Fut: Future, F: FnMut(Timing) {
+        recorder: Recorder,
+        op: F,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedIncremental<Fut, F>
+where
+    Fut: Future,
+    F: FnMut(Timing),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            op,
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedIncremental<Fut, F>
+where
+    Fut: Future,
+    F: FnMut(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let timing = this.recorder.timing;
+                // The next session starts with the next poll, like the first one did
+                *this.recorder = Recorder::new();
+                (this.op)(timing);
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Joined futures reporting the timing of each of them.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::future::{join_all, JoinAll};
+use pin_project_lite::pin_project;
+
+use crate::timed::TimedCollect;
+use crate::Timing;
+
+/// Instrument each of the `futures` and wait for all of them to complete.
+///
+/// Like [`join_all`], all futures are polled concurrently on the current task. The output is a
+/// vector of the outputs and a vector of their timings, both in the order of `futures`, so that
+/// `timings[i]` belongs to `outputs[i]`. The futures run concurrently, so their busy times are
+/// interleaved and their idle times overlap in wall-clock time: neither sums up to the time the
+/// join took. Use [`timed_collect`](super::timed_collect) on the join itself for that. Requires
+/// the `futures` feature.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_join_all;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let branches = [30, 10, 20].map(|ms| async move {
+///     tokio::time::sleep(Duration::from_millis(ms)).await;
+///     ms
+/// });
+///
+/// let (outputs, timings) = timed_join_all(branches).await;
+///
+/// assert_eq!(outputs, [30, 10, 20]);
+/// # if !cfg!(feature = "disabled") {
+/// assert!(timings[0].idle > timings[1].idle);
+/// # }
+/// # }
+pub fn timed_join_all<I>(futures: I) -> TimedJoinAll<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    TimedJoinAll {
+        inner: join_all(futures.into_iter().map(TimedCollect::new)),
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_join_all`] function.
+    pub struct TimedJoinAll<Fut> where Fut: Future {
+        #[pin]
+        inner: JoinAll<TimedCollect<Fut>>,
+    }
+}
+
+impl<Fut> Future for TimedJoinAll<Fut>
+where
+    Fut: Future,
+{
+    type Output = (Vec<Fut::Output>, Vec<Timing>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project()
+            .inner
+            .poll(cx)
+            .map(|results| results.into_iter().unzip())
+    }
+}
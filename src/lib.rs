@@ -67,23 +67,266 @@
 //!
 //! Note that in that case you measure the combined time for all wrapped futures.
 //!
+//! # Shared futures
+//!
+//! A future wrapped in [`Shared`](https://docs.rs/futures/latest/futures/future/struct.Shared.html)
+//! is polled by whichever clone happens to be polled, and its output is cached for all other
+//! clones. Where the instrumentation sits therefore makes a difference:
+//!
+//! - Instrumenting _inside_, i.e. `fut.timed(f).shared()`, measures the underlying work exactly
+//!   once. Busy time is the time spent polling it, regardless of which clone drove the poll,
+//!   idle time are the gaps in between, and the closure is called once.
+//! - Instrumenting _outside_, i.e. each clone with `shared.clone().timed(f)`, measures each
+//!   clone. The busy time of the underlying work is attributed to the clones that happened to
+//!   poll it, while clones finding a cached output report next to no busy time.
+//!
+//! Instrument inside to measure the work and outside to measure the waiting of each consumer.
+//!
+//! # Features
+//!
+//! - `disabled`: turn all combinators into transparent wrappers that only poll the inner future.
+//!   No time is measured and no closure is called, which allows building the same code with and
+//!   without instrumentation overhead by toggling a feature instead of changing call sites. Only
+//!   `timed_controllable`, whose closure decides the outcome of the future, keeps measuring.
+//! - `tokio`: enable `offload_if_busy` which moves work to a blocking thread if its first poll
+//!   was busy for too long, `throttle_if_busy` which delays futures that are busy for too long,
+//!   `timed_oneshot` which sends the timing through a oneshot channel, `timed_timeout` which
+//!   gives up after a timeout and `timed_watch` which publishes the timing while a future runs.
+//! - `tracing`: enable `emit_timing_event` which emits a [tracing] event with the timing of a
+//!   completed future, `emit_console_event` which does so with the field names of tokio-console,
+//!   `log_timing` which logs a single line at a given level and `trace_each_poll` which opens a
+//!   span around every poll.
+//! - `cpu-time`: measure the busy time with the per-thread CPU clock instead of wall-clock time.
+//!   Time the thread was descheduled by the operating system in the middle of a poll is then not
+//!   counted as busy. Only supported on Linux, other platforms fall back to wall-clock time. Also
+//!   enables `warn_if_cpu` which only warns about polls that used a lot of CPU time.
+//! - `futures`: enable `TimedUnordered`, a `FuturesUnordered` aggregating the timing of all
+//!   its futures, and `timed_join_all` which joins futures and returns the timing of each.
+//! - `chrome-trace`: enable `timed_chrome` which records polls as Chrome trace events for
+//!   `chrome://tracing` and Perfetto.
+//! - `test-util`: enable `MockClock` to test instrumented futures with exact busy and idle
+//!   times.
+//! - `defmt`: enable `warn_slow_defmt` which logs slow polls with [defmt].
+//! - `backtrace`: enable `warn_if_trace` which passes a backtrace of slow polls to the closure.
+//! - `serde`: derive `Serialize` and `Deserialize` for `TimingBaseline` to save and load
+//!   baselines.
+//! - `quanta`: read wall-clock time from the [quanta] clock instead of [`std::time::Instant`].
+//!   On CPUs with a stable TSC this is considerably cheaper, which matters for futures polled
+//!   very often. quanta calibrates the TSC against the system clock on first use, which takes a
+//!   short while, and falls back to the system clock if the TSC is not reliable. Readings are
+//!   mapped onto a standard [`std::time::Instant`] taken at the first measurement, so long
+//!   running processes may see a small drift compared to instants obtained from the standard
+//!   library.
+//! - `opentelemetry`: enable `timed_otel` which records the busy and idle time as attributes and
+//!   an event of the current [OpenTelemetry] span.
+//! - `statsd`: enable `timed_statsd` and `timed_statsd_tagged` which send the busy time as a
+//!   timer metric through a [cadence] Statsd client, with Datadog tags for the latter.
+//!
 //! # License
 //!
 //! This project is licensed under the [MIT license].
 //!
+//! [OpenTelemetry]: https://docs.rs/opentelemetry/latest/opentelemetry/
 //! [MIT license]: https://github.com/matze/future-timed/blob/main/LICENSE
 //! [future-timing]: https://docs.rs/future-timing/latest/future_timing/
+//! [cadence]: https://docs.rs/cadence/latest/cadence/
+//! [defmt]: https://docs.rs/defmt/latest/defmt/
 //! [futures]: https://docs.rs/futures/latest/futures/index.html
+//! [quanta]: https://docs.rs/quanta/latest/quanta/
+//! [tracing]: https://docs.rs/tracing/latest/tracing/
 
 use std::future::Future;
 
+mod abort;
+mod accumulate;
+mod adaptive;
+mod assert;
+mod baseline;
+mod channel;
+#[cfg(feature = "chrome-trace")]
+mod chrome;
+mod clock;
+mod concurrent;
+mod control;
+#[cfg(feature = "cpu-time")]
+mod cpu;
+mod ctx;
+mod deadline;
+mod detailed;
+mod elapsed;
+mod error;
+#[cfg(feature = "tracing")]
+mod event;
+mod flame;
+mod gated;
+mod global;
+mod histogram;
+mod idle;
+mod incremental;
+#[cfg(feature = "futures")]
+mod join;
+mod local;
+mod marks;
+#[cfg(feature = "tokio")]
+mod offload;
+#[cfg(feature = "tokio")]
+mod oneshot;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+mod precise;
+mod recent;
+mod registry;
+mod resolution;
+mod retry;
+mod scope;
+mod select;
+mod sink;
+mod slow;
+#[cfg(feature = "defmt")]
+mod slow_defmt;
+#[cfg(feature = "statsd")]
+mod statsd;
+mod summary;
+#[cfg(feature = "tokio")]
+mod throttle;
 mod timed;
+#[cfg(feature = "tokio")]
+mod timeout;
+#[cfg(feature = "backtrace")]
+mod trace;
+mod tree;
+mod units;
+#[cfg(feature = "futures")]
+mod unordered;
+mod utilization;
+mod wakes;
+mod wall;
 mod warn;
+#[cfg(feature = "tokio")]
+mod watch;
+mod when;
+mod yielding;
 
-pub use timed::{timed, Timed, Timing};
-pub use warn::{warn_if, WarnIf};
+pub use abort::{Aborted, TimedControllable};
+pub use accumulate::{TimedAccumulate, TimingAccumulator};
+pub use adaptive::{SamplerState, TimedAdaptive};
+pub use assert::AssertIdleUnder;
+pub use baseline::{TimedRegressionCheck, TimingBaseline};
+pub use channel::{timed_channel, TimedChannel};
+#[cfg(feature = "chrome-trace")]
+pub use chrome::{ChromeTrace, TimedChrome};
+pub use clock::clock_resolution;
+#[cfg(feature = "test-util")]
+pub use clock::MockClock;
+pub use concurrent::{timed_intervals, BusyInterval, ConcurrentTiming, TimedIntervals};
+pub use control::{skip_timing, SkipScope, SkipTiming, TimedControlled, TimingControl};
+#[cfg(feature = "cpu-time")]
+pub use cpu::{warn_if_cpu, WarnIfCpu};
+pub use ctx::{timed_ctx, TimedCtx};
+pub use deadline::{timed_deadline, TimedDeadline};
+pub use detailed::{DetailedTiming, TimedDetailed};
+pub use elapsed::{elapsed, lifetime, Elapsed, Lifetime, TimedLightweight};
+pub use error::TimedErrContext;
+#[cfg(feature = "tracing")]
+pub use event::{EmitConsoleEvent, EmitTimingEvent, LogTiming, TraceEachPoll};
+pub use flame::{take_flame_folded, TimedFlame};
+pub use gated::{GatedTiming, TimedGated};
+pub use global::{set_global_reporter, SetGlobalReporterError};
+pub use histogram::{FixedHistogram, TimedFixedHist, TimedIdleHist, TimingHistogram};
+pub use idle::{timed_if_idle_bound, TimedIfIdleBound};
+pub use incremental::TimedIncremental;
+#[cfg(feature = "futures")]
+pub use join::{timed_join_all, TimedJoinAll};
+pub use local::{take_thread_local_timing, TimedThreadLocal};
+pub use marks::{timing_mark_end, timing_mark_start, TimedMarked};
+#[cfg(feature = "tokio")]
+pub use offload::{offload_if_busy, OffloadIfBusy};
+#[cfg(feature = "tokio")]
+pub use oneshot::TimedOneshot;
+#[cfg(feature = "opentelemetry")]
+pub use otel::TimedOtel;
+pub use precise::{IdleKind, PreciseIdle, TimedIdleDefinition, TimedPreciseIdle};
+pub use recent::{RecentTimings, TimedRecent};
+pub use registry::{
+    RegistryEntry, TimedNamedHist, TimedNamedOwned, TimingRegistry, VariantComparison,
+};
+pub use resolution::TimedAboveResolution;
+pub use retry::RetryTiming;
+pub use scope::{timing_scope, TimingGuard, TimingScope};
+pub use select::{timed_select, SelectTiming, Selected, TimedSelect};
+pub use sink::{FnSink, NoopSink, TimedSink, TimingSink};
+pub use slow::{timed_if_slower_than, TimedIfSlowerThan};
+#[cfg(feature = "defmt")]
+pub use slow_defmt::WarnSlowDefmt;
+#[cfg(feature = "statsd")]
+pub use statsd::TimedStatsd;
+pub use summary::{summarize, TimingSummary};
+#[cfg(feature = "tokio")]
+pub use throttle::ThrottleIfBusy;
+pub use timed::{
+    timed, timed_collect, timed_poll_fn, BoxTimed, Timed, TimedCollect, Timing, TimingBuilder,
+};
+#[cfg(feature = "tokio")]
+pub use timeout::TimedTimeout;
+#[cfg(feature = "backtrace")]
+pub use trace::{warn_if_trace, WarnIfTrace};
+pub use tree::{TimedNode, TimedTree, TimingNode};
+pub use units::{Nanos, Threshold};
+#[cfg(feature = "futures")]
+pub use unordered::{BatchSummary, TimedUnordered};
+pub use utilization::{TimedUtilization, UtilizationTracker};
+pub use wakes::{TraceWakes, WakeEvent};
+pub use wall::{timed_vs_wall, TimedVsWall};
+pub use warn::{
+    warn_if, warn_if_after_warmup, warn_if_async, warn_if_cancelled, warn_if_first_poll,
+    warn_if_graded, WarnIf, WarnIfAfterWarmup, WarnIfAsync, WarnIfCancelled, WarnIfFirstPoll,
+    WarnIfGraded,
+};
+#[cfg(feature = "tokio")]
+pub use watch::{TimedWatch, TimingSnapshots};
+pub use when::TimedWhen;
+pub use yielding::YieldAfter;
 
 /// An extension trait for `Future`s that adds the [`timed`] method.
+///
+/// The trait is implemented for all futures, including the `impl Future` returned by calling an
+/// `async fn`, so its methods can be called on such a call directly without boxing. All methods
+/// take the future by value and thus require it to be [`Sized`]. Trait objects are not, but boxed
+/// and pinned ones are: instrument a `Pin<Box<dyn Future>>` instead of a `dyn Future`, or a
+/// pinned reference `Pin<&mut F>` to keep using the future afterwards.
+///
+/// The output is passed through unchanged and may borrow from data the future captured, e.g. an
+/// `async fn` returning `&'a str`: no method requires it to be `'static`. Closures receiving the
+/// output, like the predicate of [`timed_when`](Self::timed_when), get it by reference and can
+/// inspect borrowed data but not keep the reference beyond the call, so clone what they need to
+/// store. Only `offload_if_busy`, which may move the work to another thread, requires the output
+/// to be `Send + 'static`; return owned data from futures passed to it.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::TimedFutureExt;
+/// use std::future::Future;
+/// use std::pin::Pin;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// async fn answer() -> u64 {
+///     42
+/// }
+///
+/// // Generic code instrumenting any future
+/// async fn report<T>(fut: impl Future<Output = T>) -> T {
+///     fut.timed(|timing| println!("{timing:?}")).await
+/// }
+///
+/// let boxed: Pin<Box<dyn Future<Output = u64>>> = Box::pin(answer());
+///
+/// assert_eq!(answer().timed(|_| {}).await, 42);
+/// assert_eq!(report(answer()).await, 42);
+/// assert_eq!(boxed.timed(|_| {}).await, 42);
+/// # }
+/// ```
 pub trait TimedFutureExt: Future {
     /// Instrument a future to record its timing.
     ///
@@ -94,13 +337,14 @@ pub trait TimedFutureExt: Future {
     ///
     /// ```
     /// use future_timed::{TimedFutureExt, Timing};
-    /// # use std::time::Duration;
+    /// # use std::time::{Duration, Instant};
     /// # #[tokio::main]
     /// # async fn main() {
     ///
     /// let output = async {
     ///         // Block the executor
-    ///         std::thread::sleep(Duration::from_micros(200));
+    ///         let start = Instant::now();
+    ///         while start.elapsed() < Duration::from_micros(200) {}
     ///         tokio::time::sleep(Duration::from_micros(10)).await;
     ///     42
     ///     }.timed(|Timing { idle, busy }| {
@@ -119,8 +363,43 @@ pub trait TimedFutureExt: Future {
         Timed::new(self, f)
     }
 
-    /// Instrument a future call a closure if a certain threshold is exceeded. The closure is
-    /// called for _each_ poll that exceeds the threshold.
+    /// Instrument a future like [`timed`](Self::timed) but box the closure, so that the returned
+    /// [`BoxTimed`] can be named, e.g. to store it in a struct field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{BoxTimed, TimedFutureExt};
+    /// use tokio::time::Sleep;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// struct Connection {
+    ///     keepalive: BoxTimed<Sleep>,
+    /// }
+    ///
+    /// let connection = Connection {
+    ///     keepalive: tokio::time::sleep(Duration::from_millis(1)).timed_boxed_closure(|timing| {
+    ///         println!("keepalive after {timing:?}");
+    ///     }),
+    /// };
+    ///
+    /// connection.keepalive.await;
+    /// # }
+    fn timed_boxed_closure<F>(self, f: F) -> BoxTimed<Self>
+    where
+        Self: Sized,
+        F: FnOnce(Timing) + Send + 'static,
+    {
+        Timed::new(self, Box::new(f))
+    }
+
+    /// Instrument a future to return its timing together with its output.
+    ///
+    /// This is the closure-free primitive the closure-based methods are equivalent to:
+    /// `fut.timed(f)` behaves like `fut.timed_collect()` followed by passing the timing to `f`.
+    /// Use it when what to do with the timing is only decided after completion.
     ///
     /// # Examples
     ///
@@ -130,24 +409,2200 @@ pub trait TimedFutureExt: Future {
     /// # #[tokio::main]
     /// # async fn main() {
     ///
+    /// let (output, timing) = async { 42 }.timed_collect().await;
+    ///
+    /// if timing.exceeds_total(Duration::from_secs(1)) {
+    ///     println!("slow: {timing:?}");
+    /// }
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_collect(self) -> TimedCollect<Self>
+    where
+        Self: Sized,
+    {
+        TimedCollect::new(self)
+    }
+
+    /// Instrument a future to count its busy time in a [`FixedHistogram`] on completion.
+    ///
+    /// Neither the histogram nor the returned future allocate, which makes this suitable for
+    /// profiling on memory-constrained targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{FixedHistogram, TimedFutureExt};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut histogram = FixedHistogram::new([Duration::from_millis(1)]);
+    ///
+    /// let output = async { 42 }.timed_fixed_hist(&mut histogram).await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(histogram.count(), 1);
+    /// # }
+    /// # }
+    fn timed_fixed_hist<const N: usize>(
+        self,
+        histogram: &mut FixedHistogram<N>,
+    ) -> TimedFixedHist<'_, Self>
+    where
+        Self: Sized,
+    {
+        TimedFixedHist::new(self, histogram)
+    }
+
+    /// Instrument a future to count each idle gap between two polls in a [`TimingHistogram`].
+    ///
+    /// Unlike the busy time histograms, which count one duration per future, this counts one
+    /// duration per poll after the first as soon as it happens. The distribution of gaps tells
+    /// apart futures woken steadily from bursty ones, and
+    /// [`percentile`](TimingHistogram::percentile) gives their wakeup latencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingHistogram};
+    /// use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut histogram = TimingHistogram::with_buckets(&[Duration::from_millis(1)]);
+    ///
+    /// async {
+    ///     for _ in 0..3 {
+    ///         tokio::time::sleep(Duration::from_millis(2)).await;
+    ///     }
+    /// }
+    /// .timed_idle_hist(&mut histogram)
+    /// .await;
+    ///
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(histogram.count(), 3);
+    /// assert_eq!(histogram.percentile(0.5), Some(Duration::MAX));
+    /// # }
+    /// # }
+    fn timed_idle_hist(self, histogram: &mut TimingHistogram) -> TimedIdleHist<'_, Self>
+    where
+        Self: Sized,
+    {
+        TimedIdleHist::new(self, histogram)
+    }
+
+    /// Instrument a future to record its timing along with per-poll statistics.
+    ///
+    /// The closure receives a [`DetailedTiming`] with the number of polls, the busy time of the
+    /// longest, the first and the final poll and how often the waker changed in addition to the
+    /// busy and idle time. Tracking these costs a few comparisons and additions per poll and a
+    /// clone of the waker on the first poll on top of [`timed`](TimedFutureExt::timed), which is
+    /// negligible compared to reading the clock but makes the future slightly larger.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
     /// let output = async {
-    ///     // Block the executor
-    ///     std::thread::sleep(Duration::from_micros(200));
+    ///     tokio::task::yield_now().await;
     ///     42
     /// }
-    /// .warn_if(Duration::from_micros(10), |duration| {
-    ///     assert!(duration >= Duration::from_micros(200));
+    /// .timed_detailed(|detailed| {
+    ///     assert_eq!(detailed.polls, 2);
+    ///     assert!(detailed.max_poll <= detailed.busy);
+    ///     assert!(detailed.final_poll_busy <= detailed.max_poll);
+    ///     assert_eq!(detailed.waker_changes, 0);
     /// })
     /// .await;
     ///
     /// assert_eq!(output, 42);
     /// # }
-    fn warn_if<F>(self, threshold: std::time::Duration, f: F) -> WarnIf<Self, F>
+    fn timed_detailed<F>(self, f: F) -> TimedDetailed<Self, F>
     where
         Self: Sized,
-        F: Fn(std::time::Duration),
+        F: FnOnce(DetailedTiming),
+    {
+        TimedDetailed::new(self, f)
+    }
+
+    /// Instrument a future like [`timed_detailed`](TimedFutureExt::timed_detailed) and classify
+    /// each [`Poll::Pending`](std::task::Poll::Pending) as either a yield or a wait.
+    ///
+    /// Futures yielding cooperatively return pending although they could make progress right
+    /// away and are re-polled almost immediately. A pending poll followed by a re-poll with at
+    /// most `threshold` idle time is counted in [`DetailedTiming::yields`], all others in
+    /// [`DetailedTiming::waits`]. Choose the threshold above the scheduling latency of the
+    /// executor but well below the expected I/O latency.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// async {
+    ///     tokio::task::yield_now().await;
+    ///     tokio::time::sleep(Duration::from_millis(20)).await;
+    /// }
+    /// .timed_detailed_yields(Duration::from_millis(5), |detailed| {
+    ///     assert_eq!(detailed.yields, 1);
+    ///     assert_eq!(detailed.waits, 1);
+    /// })
+    /// .await;
+    /// # }
+    fn timed_detailed_yields<F>(
+        self,
+        threshold: std::time::Duration,
+        f: F,
+    ) -> TimedDetailed<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(DetailedTiming),
+    {
+        TimedDetailed::with_yield_threshold(self, Some(threshold), f)
+    }
+
+    /// Instrument a future to record its timing only if it is sampled, with a sampling rate
+    /// adapting to recent slowness.
+    ///
+    /// About 1 in `base_rate` futures sharing `state` is sampled while they are fast, and up to
+    /// every future while they are slow, see [`SamplerState`] for the control algorithm. Only
+    /// sampled futures read the clock and call `f` on completion. A `base_rate` of 0 or 1
+    /// samples every future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{SamplerState, TimedFutureExt};
+    /// use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let state = SamplerState::new(Duration::from_millis(1));
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_millis(2)).await;
+    ///     42
+    /// }
+    /// .timed_adaptive(100, &state, |timing| println!("sampled {timing:?}"))
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    ///
+    /// // The first future is always sampled, it was slow so twice as many are sampled now
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(state.interval(100), 50);
+    /// # }
+    /// # }
+    fn timed_adaptive<F>(
+        self,
+        base_rate: u32,
+        state: &SamplerState,
+        f: F,
+    ) -> TimedAdaptive<'_, Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedAdaptive::new(self, base_rate, state, f)
+    }
+
+    /// Instrument a future to call a closure after _each_ poll with a [`WakeEvent`] describing
+    /// it.
+    ///
+    /// This is a verbose poll tracer meant for debugging and teaching: the event tells whether the
+    /// poll returned [`Poll::Pending`](std::task::Poll::Pending), how long it took and how long the
+    /// future was idle since the previous poll, i.e. how long it took to be woken and polled
+    /// again. The inner future is polled exactly as without instrumentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::task::yield_now().await;
+    ///     42
+    /// }
+    /// .trace_wakes(|event| {
+    ///     println!(
+    ///         "poll #{} pending={} busy={:?} idle={:?}",
+    ///         event.poll, event.pending, event.busy, event.idle
+    ///     );
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn trace_wakes<F>(self, f: F) -> TraceWakes<Self, F>
+    where
+        Self: Sized,
+        F: Fn(WakeEvent),
+    {
+        TraceWakes::new(self, f)
+    }
+
+    /// Instrument a future to first await `gate`, e.g. a semaphore acquisition, and separate the
+    /// timing of acquiring the resource from the timing of the work done while holding it.
+    ///
+    /// The output of `gate`, e.g. the permit, is held until the instrumented future completes and
+    /// dropped right before the closure is called with the [`GatedTiming`]. High `wait` timing
+    /// points at contention while high `work` timing points at slow work. If the acquisition
+    /// happens deep inside a future instead, time it separately with
+    /// [`timing_scope`] or carve it out with [`skip_timing`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use tokio::sync::Semaphore;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let semaphore = Semaphore::new(4);
+    ///
+    /// let output = async { 42 }
+    ///     .timed_gated(semaphore.acquire(), |timing| {
+    ///         println!("waited {:?}, worked {:?}", timing.wait, timing.work);
+    ///     })
+    ///     .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// assert_eq!(semaphore.available_permits(), 4);
+    /// # }
+    fn timed_gated<G, F>(self, gate: G, f: F) -> TimedGated<Self, G, F>
+    where
+        Self: Sized,
+        G: Future,
+        F: FnOnce(GatedTiming),
+    {
+        TimedGated::new(self, gate, f)
+    }
+
+    /// Instrument a future to record its timing with an explicit definition of idle time.
+    ///
+    /// With [`IdleKind::BetweenPolls`], the default of all other combinators, idle time is all time
+    /// between polls. This includes the time between being woken and being polled again, during
+    /// which the future was ready but queued by the executor. With [`IdleKind::Waiting`], idle
+    /// time only covers the time until the future was woken, using the wrapped waker of
+    /// [`timed_precise_idle`](Self::timed_precise_idle) and its overhead. For a future sleeping
+    /// 5ms on an executor that takes another 2ms to get to it, `BetweenPolls` reports 7ms of idle
+    /// time and `Waiting` reports 5ms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{IdleKind, TimedFutureExt, Timing};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// tokio::time::sleep(Duration::from_millis(5))
+    ///     .timed_idle_definition(IdleKind::Waiting, |Timing { idle, .. }| {
+    ///         assert!(idle >= Duration::from_millis(4));
+    ///     })
+    ///     .await;
+    /// # }
+    fn timed_idle_definition<F>(self, kind: IdleKind, f: F) -> TimedIdleDefinition<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedIdleDefinition::new(self, kind, f)
+    }
+
+    /// Instrument a future to record its timing and split its idle time into waiting and
+    /// scheduling delay.
+    ///
+    /// Besides the [`Timing`], the closure receives a [`PreciseIdle`] whose `waiting` is the time
+    /// from the end of a poll until the future was woken and whose `scheduling_delay` is the time
+    /// from the wake until the executor polled it again. Their sum is the idle time. A high
+    /// scheduling delay points at an overloaded executor rather than slow I/O.
+    ///
+    /// To observe wakes, the inner future is polled with a wrapped waker. This allocates once per
+    /// future and adds a mutex and a clock read to each wake, so it is opt-in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// tokio::time::sleep(Duration::from_millis(5))
+    ///     .timed_precise_idle(|timing, precise| {
+    ///         assert_eq!(precise.waiting + precise.scheduling_delay, timing.idle);
+    ///         assert!(precise.waiting >= Duration::from_millis(4));
+    ///     })
+    ///     .await;
+    /// # }
+    fn timed_precise_idle<F>(self, f: F) -> TimedPreciseIdle<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing, PreciseIdle),
+    {
+        TimedPreciseIdle::new(self, f)
+    }
+
+    /// Instrument a future to pass a context value together with its timing to a closure.
+    ///
+    /// `ctx` is moved into the returned future and handed to the closure on completion. This is
+    /// handy to correlate timing with values computed at the call site, such as trace IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let trace_id = String::from("4bf92f35");
+    ///
+    /// let output = async { 42 }
+    ///     .timed_ctx(trace_id, |trace_id, timing| {
+    ///         assert_eq!(trace_id, "4bf92f35");
+    ///         println!("{trace_id}: {timing:?}");
+    ///     })
+    ///     .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_ctx<C, F>(self, ctx: C, f: F) -> TimedCtx<Self, C, F>
+    where
+        Self: Sized,
+        F: FnOnce(C, Timing),
+    {
+        TimedCtx::new(self, ctx, f)
+    }
+
+    /// Instrument a future to add its timing to an accumulator local to the current thread.
+    ///
+    /// The accumulated timing is retrieved with [`take_thread_local_timing`]. Without any
+    /// synchronization, this is the fastest way to aggregate timing on single-threaded executors
+    /// such as tokio's current-thread runtime. The timing is added on the thread completing the
+    /// future, so results are only meaningful if the future is always polled on the same thread.
+    /// With debug assertions enabled, polling it on different threads panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{take_thread_local_timing, TimedFutureExt};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    ///
+    /// for n in 0..10 {
+    ///     async move { n * 2 }.timed_thread_local().await;
+    /// }
+    ///
+    /// println!("{:?}", take_thread_local_timing());
+    /// # }
+    fn timed_thread_local(self) -> TimedThreadLocal<Self>
+    where
+        Self: Sized,
+    {
+        TimedThreadLocal::new(self)
+    }
+
+    /// Instrument a restartable future to report the timing of each polling session.
+    ///
+    /// Some custom drivers reuse future-like state machines that can be polled again after
+    /// returning [`Poll::Ready`](std::task::Poll::Ready). A session starts with the first poll
+    /// after creation or after the previous completion and ends with the next poll returning
+    /// `Ready`, at which point the closure is called with the timing of only that session. As with
+    /// the first poll, the time between a completion and the first poll of the next session is not
+    /// counted as idle time.
+    ///
+    /// This combinator does not change the [`Future`] contract: polling after completion is
+    /// forwarded to the inner future, which is only sound for futures documented to support it.
+    /// Most futures, including `async` blocks, panic when polled again, so use
+    /// [`timed`](Self::timed) for them instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use std::pin::pin;
+    /// use std::task::Poll;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut sessions = 0;
+    /// let mut count = 0;
+    ///
+    /// let ticks = std::future::poll_fn(|_| {
+    ///     count += 1;
+    ///     Poll::Ready(count)
+    /// });
+    ///
+    /// let mut ticks = pin!(ticks.on_complete_incremental(|_timing| sessions += 1));
+    ///
+    /// assert_eq!(ticks.as_mut().await, 1);
+    /// assert_eq!(ticks.as_mut().await, 2);
+    /// # if !cfg!(feature = "disabled") {
+    /// drop(ticks);
+    /// assert_eq!(sessions, 2);
+    /// # }
+    /// # }
+    fn on_complete_incremental<F>(self, f: F) -> TimedIncremental<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Timing),
+    {
+        TimedIncremental::new(self, f)
+    }
+
+    /// Instrument a fallible future to attach its timing to errors.
+    ///
+    /// If the future resolves to an error, the closure maps it together with the timing into a new
+    /// error, e.g. one mentioning how long the failing operation took. Successful outputs are
+    /// passed through and the closure is not called. With the `disabled` feature, the closure is
+    /// still called to convert the error but receives zero timing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let result = async { "nope".parse::<u32>() }
+    ///     .timed_err_context(|err, timing| format!("{err} after {timing:?}"))
+    ///     .await;
+    ///
+    /// assert!(result.unwrap_err().starts_with("invalid digit found in string after"));
+    /// # }
+    fn timed_err_context<F, T, E, E2>(self, f: F) -> TimedErrContext<Self, F>
+    where
+        Self: Future<Output = Result<T, E>> + Sized,
+        F: FnOnce(E, Timing) -> E2,
+    {
+        TimedErrContext::new(self, f)
+    }
+
+    /// Instrument a future to pass its timing to the global reporter.
+    ///
+    /// This avoids passing the same closure at every call site. The timing is discarded if no
+    /// reporter was installed with [`set_global_reporter`] by the time the future completes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{set_global_reporter, TimedFutureExt};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// set_global_reporter(|timing| eprintln!("request took {timing:?}")).unwrap();
+    ///
+    /// let output = async { 42 }.timed_default().await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_default(self) -> Timed<Self, fn(Timing)>
+    where
+        Self: Sized,
+    {
+        Timed::new(self, global::report)
+    }
+
+    /// Instrument a future without output to record its timing.
+    ///
+    /// This is the same as [`timed`](TimedFutureExt::timed) restricted to futures returning `()`
+    /// and makes fire-and-forget instrumentation of side-effect-only futures explicit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, Timing};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// tokio::spawn(
+    ///     async {
+    ///         println!("side effect");
+    ///     }
+    ///     .timed_void(|Timing { busy, .. }| {
+    ///         println!("busy for {busy:?}");
+    ///     }),
+    /// );
+    /// # }
+    fn timed_void<F>(self, f: F) -> Timed<Self, F>
+    where
+        Self: Future<Output = ()> + Sized,
+        F: FnOnce(Timing),
+    {
+        Timed::new(self, f)
+    }
+
+    /// Instrument a future to record the wall-clock time from its first poll to its completion.
+    ///
+    /// This skips the per-poll bookkeeping of [`timed`](TimedFutureExt::timed) and is cheaper if
+    /// only the overall duration is of interest. The reported duration is wall-clock time, not
+    /// busy time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_millis(1)).await;
+    ///     42
+    /// }
+    /// .elapsed(|elapsed| {
+    ///     assert!(elapsed >= Duration::from_millis(1));
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn elapsed<F>(self, f: F) -> Elapsed<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(std::time::Duration),
+    {
+        Elapsed::new(self, f)
+    }
+
+    /// Instrument a future to record the wall-clock time from its construction to its completion.
+    ///
+    /// In contrast to [`elapsed`](TimedFutureExt::elapsed) and the sum of busy and idle time, this
+    /// includes the time before the first poll, e.g. the scheduling delay of a spawned future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let handle = tokio::spawn(async { 42 }.lifetime(|lifetime| {
+    ///     println!("spawned future completed after {lifetime:?}");
+    /// }));
+    ///
+    /// assert_eq!(handle.await.unwrap(), 42);
+    /// # }
+    fn lifetime<F>(self, f: F) -> Lifetime<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(std::time::Duration),
+    {
+        Lifetime::new(self, f)
+    }
+
+    /// Instrument a future to record the wall-clock time from its first poll to its completion
+    /// and the busy time of its final poll.
+    ///
+    /// The closure receives the elapsed time and the final poll's busy time. Intermediate polls
+    /// only read the clock once at their start and neither busy nor idle time is accumulated, so
+    /// this is cheaper than [`timed`](TimedFutureExt::timed) for futures polled many times. It
+    /// sits between [`elapsed`](TimedFutureExt::elapsed), which reads the clock only on the first
+    /// and the last poll, and `timed`, which reads it twice per poll. Both durations are
+    /// wall-clock time, even with the `cpu-time` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_millis(1)).await;
+    ///     42
+    /// }
+    /// .timed_lightweight(|elapsed, final_poll_busy| {
+    ///     assert!(elapsed >= Duration::from_millis(1));
+    ///     assert!(final_poll_busy <= elapsed);
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_lightweight<F>(self, f: F) -> TimedLightweight<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(std::time::Duration, std::time::Duration),
+    {
+        TimedLightweight::new(self, f)
+    }
+
+    /// Instrument a future to record its timing and the wall-clock time from first poll to
+    /// completion.
+    ///
+    /// The closure receives the [`Timing`] and the wall-clock [`Duration`](std::time::Duration)
+    /// so that the accounted busy and idle time can be compared against the actual lifetime of
+    /// the future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, Timing};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_micros(10)).await;
+    ///     42
+    /// }
+    /// .timed_vs_wall(|Timing { idle, busy }, wall| {
+    ///     let unaccounted = wall - (idle + busy);
+    ///     assert!(unaccounted < wall);
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_vs_wall<F>(self, f: F) -> TimedVsWall<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing, std::time::Duration),
+    {
+        TimedVsWall::new(self, f)
+    }
+
+    /// Instrument a future to report only the timing between explicit marks.
+    ///
+    /// Inside the future, call [`timing_mark_start`] and [`timing_mark_end`] around the section of
+    /// interest, even across `.await` points. The closure receives the busy time spent between
+    /// the marks and the idle time of the section, i.e. its wall-clock time minus the busy time.
+    /// Multiple sections are summed up. A section that is started but never ended is measured
+    /// until the future completes, an end without a start is ignored. Without any marks, the
+    /// closure receives zero timing.
+    ///
+    /// The marks must be set by the instrumented future itself while it is being polled, marks
+    /// set in spawned tasks or on other threads are not seen. With nested marked futures, marks
+    /// apply to the innermost one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{timing_mark_end, timing_mark_start, TimedFutureExt, Timing};
+    /// # use std::time::{Duration, Instant};
+    /// # fn spin(duration: Duration) {
+    /// #     let start = Instant::now();
+    /// #     while start.elapsed() < duration {}
+    /// # }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// async {
+    ///     // Setup not measured
+    ///     spin(Duration::from_millis(10));
+    ///
+    ///     timing_mark_start();
+    ///     tokio::time::sleep(Duration::from_millis(1)).await;
+    ///     spin(Duration::from_millis(1));
+    ///     timing_mark_end();
+    /// }
+    /// .timed_marked(|Timing { idle, busy }| {
+    ///     assert!(busy < Duration::from_millis(10));
+    ///     assert!(idle >= Duration::from_millis(1));
+    /// })
+    /// .await;
+    /// # }
+    fn timed_marked<F>(self, f: F) -> TimedMarked<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedMarked::new(self, f)
+    }
+
+    /// Instrument a future to record its timing with the ability to pause and resume counting
+    /// busy time.
+    ///
+    /// Returns the instrumented future and a [`TimingControl`] handle. Inside the future, the
+    /// handle is also available via [`TimingControl::current`]. Busy time spent while paused is
+    /// not counted. To exclude whole sub-futures declaratively, wrap them with [`skip_timing`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, Timing, TimingControl};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let (fut, _control) = async {
+    ///     let control = TimingControl::current().unwrap();
+    ///     control.pause();
+    ///     // Block the executor without being accounted for
+    ///     std::thread::sleep(Duration::from_millis(10));
+    ///     control.resume();
+    ///     42
+    /// }
+    /// .timed_controlled(|Timing { busy, .. }| {
+    ///     assert!(busy < Duration::from_millis(10));
+    /// });
+    ///
+    /// assert_eq!(fut.await, 42);
+    /// # }
+    fn timed_controlled<F>(self, f: F) -> (TimedControlled<Self, F>, TimingControl)
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedControlled::new(self, f)
+    }
+
+    /// Instrument a future to pass its running timing to a closure after each pending poll,
+    /// which can abort the future.
+    ///
+    /// If the closure returns [`ControlFlow::Break`](std::ops::ControlFlow::Break), the inner
+    /// future is dropped without being polled again and the instrumented future resolves to an
+    /// [`Aborted`] error carrying the timing up to then. Otherwise, it resolves to `Ok` with the
+    /// output of the inner future. The closure is not called after the final poll, so a future
+    /// that completed is never discarded. This allows custom abort policies based on live timing,
+    /// e.g. giving up on a future that was busy for too long in total.
+    ///
+    /// Since aborting changes the outcome of the future, it keeps measuring and calling the
+    /// closure with the `disabled` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, Timing};
+    /// use std::ops::ControlFlow;
+    /// # use std::time::{Duration, Instant};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let result = async {
+    ///     loop {
+    ///         // Heavy work blocking the executor
+    ///         let start = Instant::now();
+    ///         while start.elapsed() < Duration::from_millis(2) {}
+    ///         tokio::task::yield_now().await;
+    ///     }
+    /// }
+    /// .timed_controllable(|Timing { busy, .. }| {
+    ///     if busy > Duration::from_millis(5) {
+    ///         ControlFlow::Break(())
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// })
+    /// .await;
+    ///
+    /// assert!(result.unwrap_err().timing().busy > Duration::from_millis(5));
+    /// # }
+    fn timed_controllable<F>(self, f: F) -> TimedControllable<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Timing) -> std::ops::ControlFlow<()>,
+    {
+        TimedControllable::new(self, f)
+    }
+
+    /// Instrument a future to record its timing and the wall-clock interval of each poll.
+    ///
+    /// The intervals can be fed into a [`ConcurrentTiming`] to compute the busy time of
+    /// concurrently running futures without counting overlapping polls twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async { 42 }
+    ///     .timed_intervals(|timing, intervals| {
+    ///         assert_eq!(intervals.len(), 1);
+    ///         assert!(intervals[0].end - intervals[0].start >= timing.busy);
+    ///     })
+    ///     .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_intervals<F>(self, f: F) -> TimedIntervals<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing, Vec<BusyInterval>),
+    {
+        TimedIntervals::new(self, f)
+    }
+
+    /// Instrument a future to send its timing to a channel on completion.
+    ///
+    /// This decouples measuring from reporting, a single consumer can aggregate the timing of
+    /// many futures. If the receiver has been dropped, the timing is discarded silently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let (tx, rx) = std::sync::mpsc::channel();
+    ///
+    /// for n in 0..3 {
+    ///     async move { n }.timed_channel(tx.clone()).await;
+    /// }
+    ///
+    /// # if !cfg!(feature = "disabled") {
+    /// drop(tx);
+    /// assert_eq!(rx.iter().count(), 3);
+    /// # }
+    /// # }
+    fn timed_channel(self, tx: std::sync::mpsc::Sender<Timing>) -> TimedChannel<Self>
+    where
+        Self: Sized,
+    {
+        TimedChannel::new(self, tx)
+    }
+
+    /// Instrument a future to publish snapshots of its timing every `period` while it runs.
+    ///
+    /// The returned [`TimingSnapshots`] stream yields the timing accumulated so far every
+    /// `period`, including the idle time since the most recent poll, and the final timing on
+    /// completion. It ends once the future completed or was dropped. Ticks wake the task but only
+    /// poll the future if it was woken itself. Snapshots are buffered until consumed, so none are
+    /// lost to a slow consumer. The buffer is unbounded, so a stream that is held but not polled
+    /// grows by one snapshot per `period` until the future completes; drop the stream if the
+    /// snapshots are not needed. Like [`tokio::time::interval`], this must be called within a
+    /// tokio runtime with the time driver enabled. Requires the `tokio` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero, also with the `disabled` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use futures::StreamExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let (fut, mut snapshots) = tokio::time::sleep(Duration::from_millis(50))
+    ///     .timed_watch(Duration::from_millis(10));
+    ///
+    /// let monitor = tokio::spawn(async move {
+    ///     while let Some(timing) = snapshots.next().await {
+    ///         println!("so far: {timing:?}");
+    ///     }
+    /// });
+    ///
+    /// fut.await;
+    /// monitor.await.unwrap();
+    /// # }
+    #[cfg(feature = "tokio")]
+    fn timed_watch(self, period: std::time::Duration) -> (TimedWatch<Self>, TimingSnapshots)
+    where
+        Self: Sized,
+    {
+        TimedWatch::new(self, period)
+    }
+
+    /// Instrument a future to record its timing and give up after `limit`.
+    ///
+    /// This combines [`tokio::time::timeout`] with [`timed`](TimedFutureExt::timed): the closure
+    /// is called both if the future completes in time and if it times out, with the timing
+    /// accumulated until then. Like [`tokio::time::timeout`], this must be called within a tokio
+    /// runtime with the time driver enabled. Requires the `tokio` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let result = tokio::time::sleep(Duration::from_secs(10))
+    ///     .timed_timeout(Duration::from_millis(1), |timing| {
+    ///         println!("gave up after {timing:?}");
+    ///     })
+    ///     .await;
+    ///
+    /// assert!(result.is_err());
+    /// # }
+    #[cfg(feature = "tokio")]
+    fn timed_timeout<F>(self, limit: std::time::Duration, f: F) -> TimedTimeout<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedTimeout::new(self, limit, f)
+    }
+
+    /// Instrument a future to send its timing through a oneshot channel on completion.
+    ///
+    /// The returned receiver can be awaited by a separate task, decoupling the instrumented future
+    /// from processing its timing. If the future is dropped before completion, the receiver
+    /// resolves to an error instead. Requires the `tokio` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let (fut, rx) = async { 42 }.timed_oneshot();
+    ///
+    /// let reporter = tokio::spawn(async move {
+    ///     if let Ok(timing) = rx.await {
+    ///         println!("{timing:?}");
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(fut.await, 42);
+    /// reporter.await.unwrap();
+    /// # }
+    #[cfg(feature = "tokio")]
+    fn timed_oneshot(self) -> (TimedOneshot<Self>, tokio::sync::oneshot::Receiver<Timing>)
+    where
+        Self: Sized,
+    {
+        TimedOneshot::new(self)
+    }
+
+    /// Instrument a future to report its timing to a [`TimingSink`] on completion.
+    ///
+    /// The sink is dynamically dispatched, so the reporting destination can be chosen at runtime
+    /// without adding a closure type parameter to the instrumented future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{FnSink, NoopSink, TimedFutureExt, TimingSink};
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let verbose = true;
+    ///
+    /// let sink: Arc<dyn TimingSink> = if verbose {
+    ///     Arc::new(FnSink(|timing| println!("{timing:?}")))
+    /// } else {
+    ///     Arc::new(NoopSink)
+    /// };
+    ///
+    /// let output = async { 42 }.timed_sink(sink).await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_sink(self, sink: std::sync::Arc<dyn TimingSink>) -> TimedSink<Self>
+    where
+        Self: Sized,
+    {
+        TimedSink::new(self, sink)
+    }
+
+    /// Instrument a future to record its busy time under `label` for a flamegraph.
+    ///
+    /// Futures instrumented this way which are first polled from within another one nest below
+    /// its label. Use [`take_flame_folded`] to obtain the collected data in folded stack format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{take_flame_folded, TimedFutureExt};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async { 42 }.timed_flame("answer").await;
+    ///
+    /// assert_eq!(output, 42);
+    /// println!("{}", take_flame_folded());
+    /// # }
+    fn timed_flame(self, label: &'static str) -> TimedFlame<Self>
+    where
+        Self: Sized,
+    {
+        TimedFlame::new(self, label)
+    }
+
+    /// Instrument a future to record each of its polls as a Chrome trace event named `name`.
+    ///
+    /// The events are added to `trace`, which renders them for `chrome://tracing` or Perfetto
+    /// with [`ChromeTrace::to_json`]. Recording allocates for every poll, so this is meant for
+    /// profiling sessions rather than always-on instrumentation. Requires the `chrome-trace`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{ChromeTrace, TimedFutureExt};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let trace = ChromeTrace::new();
+    ///
+    /// tokio::task::yield_now().timed_chrome("yield", &trace).await;
+    ///
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(trace.len(), 2);
+    /// # }
+    ///
+    /// // Save to a file and open it in Perfetto
+    /// let json = trace.to_json();
+    /// # }
+    #[cfg(feature = "chrome-trace")]
+    fn timed_chrome<'a>(self, name: &'a str, trace: &'a ChromeTrace) -> TimedChrome<'a, Self>
+    where
+        Self: Sized,
+    {
+        TimedChrome::new(self, name, trace)
+    }
+
+    /// Instrument a future to feed its timing into a shared [`TimingAccumulator`].
+    ///
+    /// The accumulator is locked once when the future completes. Since the future cannot hold a
+    /// mutable borrow across `.await` points, the accumulator is shared via an [`Arc`] and a
+    /// [`Mutex`], which may also hold a trait object to switch statistics at runtime.
+    ///
+    /// [`Arc`]: std::sync::Arc
+    /// [`Mutex`]: std::sync::Mutex
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingAccumulator, TimingHistogram};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let histogram: Arc<Mutex<dyn TimingAccumulator + Send>> = Arc::new(Mutex::new(
+    ///     TimingHistogram::with_buckets(&[Duration::from_millis(1)]),
+    /// ));
+    ///
+    /// for n in 0..10 {
+    ///     async move { n * 2 }.timed_accumulate(histogram.clone()).await;
+    /// }
+    /// # }
+    fn timed_accumulate<A>(
+        self,
+        accumulator: std::sync::Arc<std::sync::Mutex<A>>,
+    ) -> TimedAccumulate<Self, A>
+    where
+        Self: Sized,
+        A: TimingAccumulator + ?Sized,
+    {
+        TimedAccumulate::new(self, accumulator)
+    }
+
+    /// Instrument a future to record its timing into `registry` under `label`, including a
+    /// histogram of its busy time.
+    ///
+    /// Use [`TimingRegistry::percentile`] to query latency percentiles per label, e.g. to check
+    /// service level objectives of individual operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingRegistry};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let registry = TimingRegistry::new();
+    ///
+    /// let output = async { 42 }.timed_named_hist("answer", &registry).await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(registry.get("answer").unwrap().count, 1);
+    /// assert!(registry.percentile("answer", 0.5).is_some());
+    /// # }
+    /// # }
+    fn timed_named_hist<'a>(
+        self,
+        label: &'static str,
+        registry: &'a TimingRegistry,
+    ) -> TimedNamedHist<'a, Self>
+    where
+        Self: Sized,
+    {
+        TimedNamedHist::new(self, label, registry)
+    }
+
+    /// Instrument a future to record its timing into `registry` as one `variant` of an operation,
+    /// e.g. of an A/B experiment comparing two implementations under production traffic.
+    ///
+    /// Variants are recorded like [`timed_named_hist`](Self::timed_named_hist) labels, including
+    /// a histogram for percentiles. Use [`TimingRegistry::compare`] to compare the mean timing of
+    /// two variants. Prefixing variants with the experiment, e.g. `"parser/v1"` and
+    /// `"parser/v2"`, keeps them apart from other labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingRegistry};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let registry = TimingRegistry::new();
+    /// let use_new_parser = true;
+    ///
+    /// let variant = if use_new_parser { "parser/v2" } else { "parser/v1" };
+    /// let output = async { 42 }.timed_variant(variant, &registry).await;
+    ///
+    /// assert_eq!(output, 42);
+    /// assert!(registry.compare("parser/v1", "parser/v2").is_none());
+    /// # }
+    fn timed_variant<'a>(
+        self,
+        variant: &'static str,
+        registry: &'a TimingRegistry,
+    ) -> TimedNamedHist<'a, Self>
+    where
+        Self: Sized,
+    {
+        TimedNamedHist::new(self, variant, registry)
+    }
+
+    /// Instrument a future to record its timing into `registry` under a label computed at runtime.
+    ///
+    /// The label is moved into the registry the first time it is seen and dropped afterwards.
+    /// Because every distinct label adds a permanent entry, only use labels from a small, bounded
+    /// set, e.g. a route template rather than the full request path. Use
+    /// [`timed`](TimedFutureExt::timed) with [`TimingRegistry::recorder`] for static labels, which
+    /// do not allocate at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingRegistry};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let registry = TimingRegistry::new();
+    /// let endpoint = "users";
+    ///
+    /// async { 42 }
+    ///     .timed_named_owned(format!("GET /{endpoint}"), &registry)
+    ///     .await;
+    ///
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(registry.get("GET /users").unwrap().count, 1);
+    /// # }
+    /// # }
+    fn timed_named_owned(
+        self,
+        label: String,
+        registry: &TimingRegistry,
+    ) -> TimedNamedOwned<'_, Self>
+    where
+        Self: Sized,
+    {
+        TimedNamedOwned::new(self, label, registry)
+    }
+
+    /// Instrument a future to record a tree of the timing of labeled sub-futures.
+    ///
+    /// Sub-futures instrumented with [`timed_node`](TimedFutureExt::timed_node) and awaited
+    /// within this future become children of the root [`TimingNode`] passed to the closure on
+    /// completion. Nodes awaited within other nodes nest accordingly. Print the root to get an
+    /// indented call tree with the busy time of each node and its share of the parent's, see
+    /// [`TimingNode`] for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     let header = async { 21 }.timed_node("header").await;
+    ///     let body = async { 2 }.timed_node("body").await;
+    ///     header * body
+    /// }
+    /// .timed_tree("request", |root| {
+    ///     assert_eq!(root.label, "request");
+    ///     assert_eq!(root.children.len(), 2);
+    ///     assert_eq!(root.children[1].label, "body");
+    ///     eprintln!("{root}");
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_tree<F>(self, label: &'static str, f: F) -> TimedTree<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(TimingNode),
+    {
+        TimedTree::new(self, label, f)
+    }
+
+    /// Instrument a future as a labeled node of an enclosing
+    /// [`timed_tree`](TimedFutureExt::timed_tree).
+    ///
+    /// If the future is not polled from within a tree, its timing is discarded.
+    fn timed_node(self, label: &'static str) -> TimedNode<Self>
+    where
+        Self: Sized,
+    {
+        TimedNode::new(self, label)
+    }
+
+    /// Instrument a future to record its timing and the slack left before `deadline`.
+    ///
+    /// The closure receives the [`Timing`] and the time between completion and the deadline,
+    /// which is zero if the deadline was missed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::{Duration, Instant};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let deadline = Instant::now() + Duration::from_micros(10);
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_millis(1)).await;
+    ///     42
+    /// }
+    /// .timed_deadline(deadline, |_, slack| {
+    ///     assert!(slack.is_zero(), "deadline was missed");
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_deadline<F>(self, deadline: std::time::Instant, f: F) -> TimedDeadline<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing, std::time::Duration),
+    {
+        TimedDeadline::new(self, deadline, f)
+    }
+
+    /// Instrument a future to report its timing on completion only if its busy time is at least
+    /// the [`clock_resolution`].
+    ///
+    /// On systems with a coarse clock, the busy time of fast futures is often measured as zero,
+    /// which skews aggregates such as means towards zero. Skipping these measurements keeps
+    /// aggregates meaningful at the expense of not counting the fastest futures at all, so report
+    /// the number of skipped futures separately if it matters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{clock_resolution, TimedFutureExt, Timing};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// async { 42 }
+    ///     .timed_above_resolution(|Timing { busy, .. }| {
+    ///         assert!(busy >= clock_resolution());
+    ///     })
+    ///     .await;
+    /// # }
+    fn timed_above_resolution<F>(self, f: F) -> TimedAboveResolution<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedAboveResolution::new(self, f)
+    }
+
+    /// Instrument a future to report its timing on completion only if `pred` returns `true` for
+    /// its output.
+    ///
+    /// The timing is always measured, but the closure is only called if the predicate holds, e.g.
+    /// only for cache misses or responses above a certain size. The predicate is called once with
+    /// a reference to the output before it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let response = async { vec![0u8; 4096] }
+    ///     .timed_when(
+    ///         |body| body.len() > 1024,
+    ///         |timing| println!("large response took {timing:?}"),
+    ///     )
+    ///     .await;
+    ///
+    /// assert_eq!(response.len(), 4096);
+    /// # }
+    fn timed_when<P, F>(self, pred: P, f: F) -> TimedWhen<Self, P, F>
+    where
+        Self: Sized,
+        P: FnOnce(&Self::Output) -> bool,
+        F: FnOnce(Timing),
+    {
+        TimedWhen::new(self, pred, f)
+    }
+
+    /// Instrument a future to report its timing on completion only if the sum of busy and idle
+    /// time exceeded `threshold`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, Timing};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_millis(1)).await;
+    ///     42
+    /// }
+    /// .timed_if_slower_than(Duration::from_micros(10), |Timing { idle, .. }| {
+    ///     assert!(idle >= Duration::from_millis(1));
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_if_slower_than<F>(
+        self,
+        threshold: std::time::Duration,
+        f: F,
+    ) -> TimedIfSlowerThan<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedIfSlowerThan::new(self, threshold, f)
+    }
+
+    /// Instrument a future to report its timing on completion only if its idle time exceeded
+    /// `ratio` times its busy time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::time::sleep(Duration::from_millis(1)).await;
+    ///     42
+    /// }
+    /// .timed_if_idle_bound(2.0, |timing| {
+    ///     println!("mostly waiting: {timing:?}");
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn timed_if_idle_bound<F>(self, ratio: f64, f: F) -> TimedIfIdleBound<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedIfIdleBound::new(self, ratio, f)
+    }
+
+    /// Instrument a future to add its busy time to a [`UtilizationTracker`] on completion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, UtilizationTracker};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let tracker = UtilizationTracker::new(Duration::from_secs(10));
+    ///
+    /// let output = async { 42 }.timed_utilization(&tracker).await;
+    ///
+    /// assert_eq!(output, 42);
+    /// assert!(tracker.utilization() < 1.0);
+    /// # }
+    fn timed_utilization(self, tracker: &UtilizationTracker) -> TimedUtilization<'_, Self>
+    where
+        Self: Sized,
+    {
+        TimedUtilization::new(self, tracker)
+    }
+
+    /// Instrument a future to push its timing into a [`RecentTimings`] ring buffer on completion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{RecentTimings, TimedFutureExt};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let recent = RecentTimings::new(100);
+    ///
+    /// let output = async { 42 }.timed_recent(&recent).await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(recent.iter().len(), 1);
+    /// # }
+    /// # }
+    fn timed_recent(self, recent: &RecentTimings) -> TimedRecent<'_, Self>
+    where
+        Self: Sized,
+    {
+        TimedRecent::new(self, recent)
+    }
+
+    /// Instrument a future to panic on completion if its idle time exceeds `limit`.
+    ///
+    /// This is meant for tests asserting that a future is not starved or waiting longer than
+    /// expected. The check only happens in builds with debug assertions enabled, otherwise the
+    /// future is passed through unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics on completion if the accumulated idle time is greater than `limit`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::task::yield_now().await;
+    ///     42
+    /// }
+    /// .assert_idle_under(Duration::from_secs(1))
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn assert_idle_under(self, limit: std::time::Duration) -> AssertIdleUnder<Self>
+    where
+        Self: Sized,
+    {
+        AssertIdleUnder::new(self, limit)
+    }
+
+    /// Instrument a future to call a closure if its busy time exceeds the busy time of `baseline`
+    /// by more than the relative `tolerance`, e.g. `0.1` for 10%.
+    ///
+    /// The closure receives the observed timing and is not called if the future stays within the
+    /// tolerance. Together with a [`TimingBaseline`] loaded from a file, this turns tests into
+    /// lightweight performance regression gates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tolerance` is negative or not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, Timing, TimingBaseline};
+    /// use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let baseline = TimingBaseline::new(Timing::builder().busy(Duration::from_secs(1)).build());
+    ///
+    /// async { 42 }
+    ///     .timed_regression_check(&baseline, 0.1, |timing| {
+    ///         panic!("busy for {:?}, expected {:?}", timing.busy, baseline.busy);
+    ///     })
+    ///     .await;
+    /// # }
+    fn timed_regression_check<F>(
+        self,
+        baseline: &TimingBaseline,
+        tolerance: f64,
+        f: F,
+    ) -> TimedRegressionCheck<'_, Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Timing),
+    {
+        TimedRegressionCheck::new(self, baseline, tolerance, f)
+    }
+
+    /// Instrument a future to emit a structured `tracing` event on completion.
+    ///
+    /// The event is emitted at the `INFO` level with the `future_timed` target and carries the
+    /// following fields:
+    ///
+    /// - `name`: the given name,
+    /// - `busy_ns` and `idle_ns`: busy and idle time in nanoseconds, matching the span attributes
+    ///   recorded by `tracing-opentelemetry` and thus picked up by Jaeger or Tempo,
+    /// - `time.busy` and `time.idle`: human readable busy and idle time, matching the fields
+    ///   `tracing-subscriber` prints for closed spans.
+    ///
+    /// Requires the `tracing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async { 42 }.emit_timing_event("answer").await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "tracing")]
+    fn emit_timing_event(self, name: &str) -> EmitTimingEvent<'_, Self>
+    where
+        Self: Sized,
+    {
+        EmitTimingEvent::new(self, name)
+    }
+
+    /// Instrument a future to log a single structured line with its timing on completion.
+    ///
+    /// The event is emitted at `level` with the `future_timed` target and renders like
+    /// `operation=name busy=1.2ms idle=340µs polls=3` with the default `tracing-subscriber`
+    /// formatter. This packages the most common use of the [`timed`](Self::timed) closure.
+    /// Requires the `tracing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use tracing::Level;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async { 42 }.log_timing(Level::DEBUG, "answer").await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "tracing")]
+    fn log_timing(self, level: tracing::Level, name: &str) -> LogTiming<'_, Self>
+    where
+        Self: Sized,
+    {
+        LogTiming::new(self, level, name)
+    }
+
+    /// Instrument a future to emit a `tracing` event on completion using the vocabulary of
+    /// tokio-console.
+    ///
+    /// tokio-console reports the busy and idle time per task, this complements it with the
+    /// timing of individual futures within a task. For the future passed to `tokio::spawn`, busy
+    /// and idle time match the values tokio-console shows for the task. The event is emitted at
+    /// the `INFO` level with the `future_timed::console` target inside the current span, i.e.
+    /// the task span if tokio is built with `tokio_unstable` and tracing. It carries the
+    /// following fields, named like the fields of tokio-console's task spans and task details:
+    ///
+    /// - `kind`: always `"future"`,
+    /// - `future.name`: the given name,
+    /// - `loc.file`, `loc.line` and `loc.col`: where the future was instrumented,
+    /// - `polls`: number of polls,
+    /// - `busy`, `idle` and `total`: busy, idle and their sum formatted like durations in
+    ///   tokio-console.
+    ///
+    /// The events are not picked up by tokio-console itself, which only instruments runtime
+    /// resources, but can be filtered by their target with any `tracing` subscriber. Requires the
+    /// `tracing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let handle = tokio::spawn(async { 42 }.emit_console_event("answer"));
+    ///
+    /// assert_eq!(handle.await.unwrap(), 42);
+    /// # }
+    #[cfg(feature = "tracing")]
+    #[track_caller]
+    fn emit_console_event(self, name: &str) -> EmitConsoleEvent<'_, Self>
+    where
+        Self: Sized,
+    {
+        EmitConsoleEvent::new(self, name, std::panic::Location::caller())
+    }
+
+    /// Instrument a future to record its timing on the current OpenTelemetry span on completion.
+    ///
+    /// The busy and idle time in nanoseconds are set as the `<name>.busy_ns` and `<name>.idle_ns`
+    /// attributes of the span, so that a trace shows how much of the span's duration the future
+    /// was busy. Additionally, an event called `name` with `busy_ns` and `idle_ns` attributes
+    /// is added, which places the completion on the span's timeline.
+    ///
+    /// The span is looked up via [`opentelemetry::Context::current`] when the future completes,
+    /// so the context carrying it must be current while the future is polled, e.g. by wrapping
+    /// the instrumented future with `opentelemetry::trace::FutureExt::with_context` or by
+    /// attaching the context on the polling thread. Spans of `tracing-opentelemetry` are only
+    /// visible this way if the context is propagated between both libraries. If there is no
+    /// active or no recording span, nothing is recorded. Requires the `opentelemetry` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use opentelemetry::trace::{FutureExt, TraceContextExt, Tracer};
+    /// use opentelemetry::{global, Context};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let span = global::tracer("app").start("request");
+    /// let cx = Context::current_with_span(span);
+    ///
+    /// let output = async { 42 }
+    ///     .timed_otel("answer")
+    ///     .with_context(cx)
+    ///     .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "opentelemetry")]
+    fn timed_otel(self, name: &str) -> TimedOtel<'_, Self>
+    where
+        Self: Sized,
+    {
+        TimedOtel::new(self, name)
+    }
+
+    /// Instrument a future to send its busy time as a Statsd timer metric on completion.
+    ///
+    /// The metric named `metric_name` is sent through `client`, a [`cadence::StatsdClient`],
+    /// which adds its prefix and default tags. The busy time is sent in milliseconds. Sending is
+    /// fire-and-forget: errors are passed to the error handler of the client instead of affecting
+    /// the future, and the client's sink decides whether sending blocks, e.g. a `UdpMetricSink`
+    /// sends a datagram right away while a `QueuingMetricSink` hands the metric to a background
+    /// thread. Requires the `statsd` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cadence::{NopMetricSink, StatsdClient};
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let client = StatsdClient::from_sink("my_app", NopMetricSink);
+    ///
+    /// let output = async { 42 }.timed_statsd(&client, "answer.busy").await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "statsd")]
+    fn timed_statsd<'a>(
+        self,
+        client: &'a cadence::StatsdClient,
+        metric_name: &'a str,
+    ) -> TimedStatsd<'a, Self>
+    where
+        Self: Sized,
+    {
+        TimedStatsd::new(self, client, metric_name, &[])
+    }
+
+    /// Instrument a future to send its busy time as a Statsd timer metric with Datadog tags on
+    /// completion.
+    ///
+    /// Like [`timed_statsd`](Self::timed_statsd) but adds the `tags` as `key:value` pairs in
+    /// addition to the default tags of the client. Tags are a Datadog extension of the Statsd
+    /// protocol understood by most modern Statsd servers. Requires the `statsd` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cadence::{NopMetricSink, StatsdClient};
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let client = StatsdClient::from_sink("my_app", NopMetricSink);
+    ///
+    /// let output = async { 42 }
+    ///     .timed_statsd_tagged(&client, "request.busy", &[("route", "/users")])
+    ///     .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "statsd")]
+    fn timed_statsd_tagged<'a>(
+        self,
+        client: &'a cadence::StatsdClient,
+        metric_name: &'a str,
+        tags: &'a [(&'a str, &'a str)],
+    ) -> TimedStatsd<'a, Self>
+    where
+        Self: Sized,
+    {
+        TimedStatsd::new(self, client, metric_name, tags)
+    }
+
+    /// Instrument a future to open a short-lived `tracing` span around _each_ poll.
+    ///
+    /// The span is entered right before and exited right after polling the inner future, so
+    /// tracing-based tools such as `tracing-timing`, `tracing-chrome` or `tracing-opentelemetry`
+    /// see the individual busy intervals instead of the single event emitted on completion by
+    /// [`emit_timing_event`](Self::emit_timing_event). Spans created by the inner future while it
+    /// is polled are children of the poll span. Each span is named `poll`, created at the `DEBUG`
+    /// level with the `future_timed` target and carries the following fields:
+    ///
+    /// - `operation`: the given name,
+    /// - `poll`: the number of the poll starting at 1,
+    /// - `busy` and `idle`: time spent in this poll and since the previous one, recorded when the
+    ///   poll returns,
+    /// - `pending`: whether the poll returned [`Poll::Pending`](std::task::Poll::Pending).
+    ///
+    /// Creating, entering and closing a span on every poll is significantly more expensive than
+    /// reading the clock and, depending on the subscriber, may allocate or take locks. Only use
+    /// this while debugging. Requires the `tracing` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     tokio::task::yield_now().await;
+    ///     42
+    /// }
+    /// .trace_each_poll("answer")
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "tracing")]
+    fn trace_each_poll(self, name: &str) -> TraceEachPoll<'_, Self>
+    where
+        Self: Sized,
+    {
+        TraceEachPoll::new(self, name)
+    }
+
+    /// Instrument a future to run an asynchronous warning if a certain threshold is exceeded. The
+    /// warning future returned by the closure is driven to completion before the instrumented
+    /// future is polled again, see the [`warn_if_async`] function for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let slow_polls = &tokio::sync::Mutex::new(Vec::new());
+    ///
+    /// let output = async {
+    ///     // Block the executor
+    ///     std::thread::sleep(Duration::from_micros(200));
+    ///     42
+    /// }
+    /// .warn_if_async(Duration::from_micros(10), |duration| async move {
+    ///     slow_polls.lock().await.push(duration);
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # if !cfg!(feature = "disabled") {
+    /// assert_eq!(slow_polls.lock().await.len(), 1);
+    /// # }
+    /// # }
+    fn warn_if_async<F, W>(self, threshold: impl Into<Threshold>, f: F) -> WarnIfAsync<Self, F, W>
+    where
+        Self: Sized,
+        F: Fn(std::time::Duration) -> W,
+        W: Future<Output = ()>,
+    {
+        WarnIfAsync::new(self, threshold.into().as_duration(), f)
+    }
+
+    /// Instrument a future call a closure if a certain threshold is exceeded. The closure is
+    /// called for _each_ poll that exceeds the threshold.
+    ///
+    /// A threshold of [`Duration::ZERO`](std::time::Duration::ZERO) calls the closure on every
+    /// poll.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     // Block the executor
+    ///     std::thread::sleep(Duration::from_micros(200));
+    ///     42
+    /// }
+    /// .warn_if(Duration::from_micros(10), |duration| {
+    ///     assert!(duration >= Duration::from_micros(200));
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn warn_if<F>(self, threshold: impl Into<Threshold>, f: F) -> WarnIf<Self, F>
+    where
+        Self: Sized,
+        F: Fn(std::time::Duration),
+    {
+        WarnIf::new(self, threshold.into().as_duration(), f)
+    }
+
+    /// Instrument a future to call a closure with a severity level if polling exceeds one of
+    /// several thresholds. The closure is called for _each_ poll that exceeds the lowest
+    /// threshold.
+    ///
+    /// The `thresholds` must be sorted in ascending order and may be anything convertible into a
+    /// [`Threshold`]. The closure receives the index of the highest exceeded threshold and the
+    /// duration of the poll.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let thresholds = [Duration::from_micros(10), Duration::from_secs(1)];
+    ///
+    /// let output = async {
+    ///     // Block the executor
+    ///     std::thread::sleep(Duration::from_micros(200));
+    ///     42
+    /// }
+    /// .warn_if_graded(thresholds, |level, _| {
+    ///     assert_eq!(level, 0);
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn warn_if_graded<F>(
+        self,
+        thresholds: impl IntoIterator<Item = impl Into<Threshold>>,
+        f: F,
+    ) -> WarnIfGraded<Self, F>
+    where
+        Self: Sized,
+        F: Fn(usize, std::time::Duration),
+    {
+        WarnIfGraded::new(self, thresholds, f)
+    }
+
+    /// Instrument a future to call a closure for _each_ poll exceeding a certain threshold after
+    /// the first `warmup_polls` polls.
+    ///
+    /// Polls during warmup are not measured at all. This avoids false alarms from expected
+    /// cold-start slowness of long-lived futures such as connections or servers, while still
+    /// catching slow polls in the steady state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     // Expensive initialization
+    ///     std::thread::sleep(Duration::from_millis(10));
+    ///     tokio::task::yield_now().await;
+    ///     42
+    /// }
+    /// .warn_if_after_warmup(Duration::from_millis(5), 1, |_| {
+    ///     unreachable!("only the warmup poll was slow");
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn warn_if_after_warmup<F>(
+        self,
+        threshold: impl Into<Threshold>,
+        warmup_polls: u32,
+        f: F,
+    ) -> WarnIfAfterWarmup<Self, F>
+    where
+        Self: Sized,
+        F: Fn(std::time::Duration),
+    {
+        WarnIfAfterWarmup::new(self, threshold.into().as_duration(), warmup_polls, f)
+    }
+
+    /// Instrument a future to call a closure if it is dropped before completion after having been
+    /// busy for at least `threshold`.
+    ///
+    /// This identifies expensive work that is thrown away on cancellation, which is easy to miss
+    /// in `select!`-heavy code. The closure receives the wasted busy time and is never called if
+    /// the future completes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::{Duration, Instant};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let expensive = async {
+    ///     let start = Instant::now();
+    ///     while start.elapsed() < Duration::from_micros(200) {}
+    ///     std::future::pending::<()>().await;
+    /// }
+    /// .warn_if_cancelled(Duration::from_micros(100), |wasted| {
+    ///     assert!(wasted >= Duration::from_micros(200));
+    /// });
+    ///
+    /// tokio::select! {
+    ///     () = expensive => unreachable!(),
+    ///     () = tokio::task::yield_now() => {},
+    /// }
+    /// # }
+    fn warn_if_cancelled<F>(self, threshold: impl Into<Threshold>, f: F) -> WarnIfCancelled<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(std::time::Duration),
+    {
+        WarnIfCancelled::new(self, threshold.into().as_duration(), f)
+    }
+
+    /// Instrument a future to call a closure if its first poll exceeds a certain threshold.
+    ///
+    /// Subsequent polls are not measured. This isolates the cold-start cost of a future which
+    /// the aggregated busy time hides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     // Expensive setup blocking the executor
+    ///     std::thread::sleep(Duration::from_micros(200));
+    ///     tokio::task::yield_now().await;
+    ///     42
+    /// }
+    /// .warn_if_first_poll(Duration::from_micros(10), |duration| {
+    ///     assert!(duration >= Duration::from_micros(200));
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn warn_if_first_poll<F>(
+        self,
+        threshold: impl Into<Threshold>,
+        f: F,
+    ) -> WarnIfFirstPoll<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(std::time::Duration),
+    {
+        WarnIfFirstPoll::new(self, threshold.into().as_duration(), f)
+    }
+
+    /// Delay the next poll of a future by `delay` after a poll was busy for longer than
+    /// `max_busy_per_poll`.
+    ///
+    /// After such a poll returned [`Poll::Pending`](std::task::Poll::Pending), the next poll
+    /// first waits for a [`tokio::time::sleep`] of `delay` to elapse before the inner future is
+    /// polled again. This self-limits a CPU-heavy future and gives other tasks a chance to run.
+    /// The future must be polled within a tokio runtime with the time driver enabled. Requires
+    /// the `tokio` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     for _ in 0..3 {
+    ///         // Heavy work blocking the executor
+    ///         std::thread::sleep(Duration::from_millis(2));
+    ///         tokio::task::yield_now().await;
+    ///     }
+    ///     42
+    /// }
+    /// .throttle_if_busy(Duration::from_millis(1), Duration::from_millis(5))
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "tokio")]
+    fn throttle_if_busy(
+        self,
+        max_busy_per_poll: std::time::Duration,
+        delay: std::time::Duration,
+    ) -> ThrottleIfBusy<Self>
+    where
+        Self: Sized,
+    {
+        ThrottleIfBusy::new(self, max_busy_per_poll, delay)
+    }
+
+    /// Instrument a future to yield to the executor before polling it again after a poll was
+    /// busy for longer than `max_poll`.
+    ///
+    /// After such a poll returned [`Poll::Pending`](std::task::Poll::Pending), the next poll
+    /// wakes the task and returns pending without polling the inner future, i.e. the task is
+    /// rescheduled and other tasks get a chance to run in between. This keeps a CPU-heavy future
+    /// cooperative without the fixed delay of `throttle_if_busy`.
+    ///
+    /// A poll cannot be interrupted, so this only forces a yield _between_ long polls, a single
+    /// long poll still blocks the executor for its whole duration. How much the yield helps
+    /// depends on the executor: tokio puts a task waking itself at the back of the run queue of
+    /// its worker, but since it does not go through `tokio::task::yield_now` it does not let
+    /// the I/O and time drivers run in between. Other executors may poll the task again right
+    /// away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     for _ in 0..3 {
+    ///         // Heavy work blocking the executor
+    ///         std::thread::sleep(Duration::from_millis(2));
+    ///         tokio::task::yield_now().await;
+    ///     }
+    ///     42
+    /// }
+    /// .yield_after(Duration::from_millis(1))
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    fn yield_after(self, max_poll: std::time::Duration) -> YieldAfter<Self>
+    where
+        Self: Sized,
+    {
+        YieldAfter::new(self, max_poll)
+    }
+
+    /// Instrument a future to log a `defmt` warning for _each_ poll exceeding `threshold`.
+    ///
+    /// The warning carries the duration of the poll in whole microseconds. The final binary must
+    /// provide a `defmt` global logger. Note that measuring still relies on the standard library
+    /// clock, so this is only usable on targets with `std` support. Requires the `defmt` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use future_timed::TimedFutureExt;
+    /// use std::time::Duration;
+    ///
+    /// let output = read_sensor()
+    ///     .warn_slow_defmt(Duration::from_millis(1))
+    ///     .await;
+    /// ```
+    #[cfg(feature = "defmt")]
+    fn warn_slow_defmt(self, threshold: impl Into<Threshold>) -> WarnSlowDefmt<Self>
+    where
+        Self: Sized,
+    {
+        WarnSlowDefmt::new(self, threshold.into().as_duration())
+    }
+
+    /// Instrument a future to call a closure if the CPU time of a poll exceeds a certain threshold.
+    /// The closure is called for _each_ poll that exceeds the threshold.
+    ///
+    /// Polls that are slow only because the thread was descheduled or blocked do not count, see
+    /// the [`warn_if_cpu`] function for details and the fallback on platforms other than
+    /// Linux. Requires the `cpu-time` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::{Duration, Instant};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     let start = Instant::now();
+    ///     while start.elapsed() < Duration::from_millis(2) {}
+    ///     42
+    /// }
+    /// .warn_if_cpu(Duration::from_micros(10), |duration| {
+    ///     eprintln!("poll used {duration:?} of CPU time");
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "cpu-time")]
+    fn warn_if_cpu<F>(self, threshold: impl Into<Threshold>, f: F) -> WarnIfCpu<Self, F>
+    where
+        Self: Sized,
+        F: Fn(std::time::Duration),
+    {
+        WarnIfCpu::new(self, threshold.into().as_duration(), f)
+    }
+
+    /// Instrument a future to call a closure with a backtrace if a certain threshold is exceeded.
+    /// The closure is called for _each_ poll that exceeds the threshold.
+    ///
+    /// The backtrace is only captured on the slow path, right after the slow poll returned.
+    /// Requires the `backtrace` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let output = async {
+    ///     // Block the executor
+    ///     std::thread::sleep(Duration::from_micros(200));
+    ///     42
+    /// }
+    /// .warn_if_trace(Duration::from_micros(10), |duration, backtrace| {
+    ///     eprintln!("poll took {duration:?} at\n{backtrace}");
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    #[cfg(feature = "backtrace")]
+    fn warn_if_trace<F>(self, threshold: impl Into<Threshold>, f: F) -> WarnIfTrace<Self, F>
+    where
+        Self: Sized,
+        F: Fn(std::time::Duration, std::backtrace::Backtrace),
     {
-        WarnIf::new(self, threshold, f)
+        WarnIfTrace::new(self, threshold.into().as_duration(), f)
     }
 }
 
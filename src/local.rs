@@ -0,0 +1,102 @@
+//! Timed future adding its timing to a thread-local accumulator.
+//!
+//! On single-threaded executors, all futures are polled on the same thread, so their timing can
+//! be summed up in a thread-local without any synchronization.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread::{self, ThreadId};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+thread_local! {
+    static ACCUMULATED: Cell<Timing> = const { Cell::new(Timing::ZERO) };
+}
+
+/// Return the timing accumulated on the current thread and reset it to zero.
+///
+/// This is the sum of the timing of all
+/// [`timed_thread_local`](super::TimedFutureExt::timed_thread_local) futures that completed on
+/// the current thread since the last call.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{take_thread_local_timing, TimedFutureExt};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+///
+/// async { 42 }.timed_thread_local().await;
+/// async { 43 }.timed_thread_local().await;
+///
+/// let timing = take_thread_local_timing();
+/// assert!(timing.idle.is_zero());
+/// assert!(take_thread_local_timing().busy.is_zero());
+/// # }
+/// ```
+#[must_use]
+pub fn take_thread_local_timing() -> Timing {
+    ACCUMULATED.with(|accumulated| accumulated.replace(Timing::ZERO))
+}
+
+pin_project! {
+    /// Future for the [`timed_thread_local`](super::TimedFutureExt::timed_thread_local) method.
+    #[derive(Debug)]
+    pub struct TimedThreadLocal<Fut> where Fut: Future {
+        recorder: Recorder,
+        thread: Option<ThreadId>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedThreadLocal<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            thread: None,
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedThreadLocal<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        if cfg!(debug_assertions) {
+            let current = thread::current().id();
+            debug_assert_eq!(
+                *this.thread.get_or_insert(current),
+                current,
+                "`timed_thread_local` future polled on different threads"
+            );
+        }
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let timing = this.recorder.timing;
+                ACCUMULATED.with(|accumulated| accumulated.set(accumulated.get() + timing));
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Timed future measuring only the section between explicit marks.
+//!
+//! While the inner future of [`timed_marked`](super::TimedFutureExt::timed_marked) is polled, its
+//! mark state is published in a thread-local, so that [`timing_mark_start`] and
+//! [`timing_mark_end`] called from within the future can read the busy time accumulated so far.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::clock::{self, Stopwatch};
+use crate::timed::Recorder;
+use crate::Timing;
+
+thread_local! {
+    static POLLING: Cell<Option<Current>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Marks {
+    /// Busy time and wall-clock time at the start mark, if within a marked section.
+    start: Option<(Duration, Instant)>,
+    /// Summed busy time of all completed sections.
+    busy: Duration,
+    /// Summed wall-clock time of all completed sections.
+    elapsed: Duration,
+}
+
+/// Mark state of the innermost marked future being polled along with its busy time before the
+/// poll and a stopwatch measuring the poll.
+#[derive(Clone, Copy, Debug)]
+struct Current {
+    marks: Marks,
+    busy: Duration,
+    stopwatch: Stopwatch,
+}
+
+impl Marks {
+    fn start(&mut self, busy: Duration, now: Instant) {
+        if self.start.is_none() {
+            self.start = Some((busy, now));
+        }
+    }
+
+    fn end(&mut self, busy: Duration, now: Instant) {
+        if let Some((start_busy, start)) = self.start.take() {
+            self.busy += busy.saturating_sub(start_busy);
+            self.elapsed += now.saturating_duration_since(start);
+        }
+    }
+
+    fn timing(&self) -> Timing {
+        Timing {
+            idle: self.elapsed.saturating_sub(self.busy),
+            busy: self.busy,
+        }
+    }
+}
+
+/// Apply `f` to the marks of the innermost marked future currently being polled, if any.
+fn with_current(f: impl FnOnce(&mut Marks, Duration, Instant)) {
+    POLLING.with(|polling| {
+        if let Some(mut current) = polling.get() {
+            let (now, busy) = current.stopwatch.stop();
+            f(&mut current.marks, current.busy.saturating_add(busy), now);
+            polling.set(Some(current));
+        }
+    });
+}
+
+/// Start the measured section of the enclosing
+/// [`timed_marked`](super::TimedFutureExt::timed_marked) future.
+///
+/// Must be called from within the marked future itself, i.e. on the same task while it is being
+/// polled. Outside of a marked future, e.g. in a spawned task, it has no effect. Calling it again
+/// before [`timing_mark_end`] has no effect either, sections do not nest.
+pub fn timing_mark_start() {
+    with_current(Marks::start);
+}
+
+/// End the measured section of the enclosing
+/// [`timed_marked`](super::TimedFutureExt::timed_marked) future.
+///
+/// Has no effect without a preceding [`timing_mark_start`]. See there for where to call it.
+pub fn timing_mark_end() {
+    with_current(Marks::end);
+}
+
+pin_project! {
+    /// Future for the [`timed_marked`](super::TimedFutureExt::timed_marked) method.
+    pub struct TimedMarked<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        recorder: Recorder,
+        marks: Marks,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedMarked<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            marks: Marks::default(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedMarked<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let current = Current {
+            marks: *this.marks,
+            busy: this.recorder.timing.busy,
+            stopwatch: Stopwatch::start(),
+        };
+
+        let previous = POLLING.with(|polling| polling.replace(Some(current)));
+        let result = this.recorder.poll(this.inner, cx);
+        let current = POLLING.with(|polling| polling.replace(previous));
+
+        if let Some(current) = current {
+            *this.marks = current.marks;
+        }
+
+        match result {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                // A section left open is measured until completion
+                let now = this.recorder.last_poll_end().unwrap_or_else(clock::now);
+                this.marks.end(this.recorder.timing.busy, now);
+
+                if let Some(op) = this.op.take() {
+                    op(this.marks.timing());
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
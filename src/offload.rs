@@ -0,0 +1,154 @@
+//! Future re-dispatching its work to a blocking thread if its first poll is busy for too long.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+use crate::clock;
+
+/// Run the future created by `make`, offloading it to a blocking thread if its first poll was
+/// busy for at least `threshold`.
+///
+/// A future that is already running cannot be moved to another thread retroactively. Instead, the
+/// future is created by calling `make` and polled once on the current task. If that poll returns
+/// [`Poll::Pending`] after being busy for at least `threshold`, the future is dropped, a fresh one
+/// is created by calling `make` again and driven to completion with [`Handle::block_on`] inside
+/// [`Handle::spawn_blocking`]. This comes with several constraints:
+///
+/// - The work done by the first attempt is thrown away, so `make` must produce futures that can
+///   be safely cancelled after their first poll and restarted from scratch.
+/// - The first, slow poll has already blocked the executor once.
+/// - If the first poll completes the future, there is nothing left to offload and the output is
+///   returned as is, no matter how long the poll took.
+/// - The offloaded future occupies a thread of the blocking pool until it completes, also while
+///   it is waiting.
+///
+/// A panic of the offloaded future is resumed when awaiting this future. Requires the `tokio`
+/// feature.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::offload_if_busy;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let handle = tokio::runtime::Handle::current();
+///
+/// let output = offload_if_busy(
+///     || async {
+///         std::thread::sleep(Duration::from_millis(10));
+///         tokio::task::yield_now().await;
+///         42
+///     },
+///     Duration::from_millis(5),
+///     handle,
+/// )
+/// .await;
+///
+/// assert_eq!(output, 42);
+/// # }
+pub fn offload_if_busy<M, Fut>(
+    make: M,
+    threshold: Duration,
+    handle: Handle,
+) -> OffloadIfBusy<M, Fut>
+where
+    M: Fn() -> Fut + Send + 'static,
+    Fut: Future,
+    Fut::Output: Send + 'static,
+{
+    OffloadIfBusy {
+        state: State::Inline {
+            first_poll_done: false,
+            inner: make(),
+        },
+        make: Some(make),
+        threshold,
+        handle,
+    }
+}
+
+pin_project! {
+    #[project = StateProj]
+    #[derive(Debug)]
+    enum State<Fut, T> {
+        Inline {
+            first_poll_done: bool,
+            #[pin]
+            inner: Fut,
+        },
+        Offloaded {
+            task: JoinHandle<T>,
+        },
+    }
+}
+
+pin_project! {
+    /// Future for the [`offload_if_busy`] function.
+    #[derive(Debug)]
+    pub struct OffloadIfBusy<M, Fut> where Fut: Future {
+        #[pin]
+        state: State<Fut, Fut::Output>,
+        make: Option<M>,
+        threshold: Duration,
+        handle: Handle,
+    }
+}
+
+impl<M, Fut> Future for OffloadIfBusy<M, Fut>
+where
+    M: Fn() -> Fut + Send + 'static,
+    Fut: Future,
+    Fut::Output: Send + 'static,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        let task = match this.state.as_mut().project() {
+            StateProj::Inline {
+                first_poll_done,
+                inner,
+            } => {
+                if *first_poll_done || cfg!(feature = "disabled") {
+                    return inner.poll(cx);
+                }
+
+                let start = clock::now();
+                let result = inner.poll(cx);
+                let busy = clock::now() - start;
+                *first_poll_done = true;
+
+                match (result, this.make.take()) {
+                    (Poll::Pending, Some(make)) if busy >= *this.threshold => {
+                        let handle = this.handle.clone();
+                        this.handle.spawn_blocking(move || handle.block_on(make()))
+                    }
+                    (result, _) => return result,
+                }
+            }
+            StateProj::Offloaded { task } => {
+                return match Pin::new(task).poll(cx) {
+                    Poll::Pending => Poll::Pending,
+                    Poll::Ready(Ok(output)) => Poll::Ready(output),
+                    Poll::Ready(Err(err)) => match err.try_into_panic() {
+                        Ok(payload) => std::panic::resume_unwind(payload),
+                        Err(err) => panic!("offloaded future failed: {err}"),
+                    },
+                }
+            }
+        };
+
+        this.state.set(State::Offloaded { task });
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -0,0 +1,65 @@
+//! Timed future sending its timing through a oneshot channel on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+use tokio::sync::oneshot;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+pin_project! {
+    /// Future for the [`timed_oneshot`](super::TimedFutureExt::timed_oneshot) method.
+    #[derive(Debug)]
+    pub struct TimedOneshot<Fut> where Fut: Future {
+        recorder: Recorder,
+        tx: Option<oneshot::Sender<Timing>>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedOneshot<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut) -> (Self, oneshot::Receiver<Timing>) {
+        let (tx, rx) = oneshot::channel();
+
+        let fut = Self {
+            recorder: Recorder::new(),
+            tx: Some(tx),
+            inner,
+        };
+
+        (fut, rx)
+    }
+}
+
+impl<Fut> Future for TimedOneshot<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(tx) = this.tx.take() {
+                    // A dropped receiver is not interested in the timing anymore.
+                    let _ = tx.send(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Timed future recording its timing on the current OpenTelemetry span on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::KeyValue;
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Nanos;
+
+/// Nanoseconds of `duration` as an OpenTelemetry integer value, saturating at `i64::MAX`.
+fn nanos(duration: Duration) -> i64 {
+    i64::try_from(Nanos::from(duration).0).unwrap_or(i64::MAX)
+}
+
+pin_project! {
+    /// Future for the [`timed_otel`](super::TimedFutureExt::timed_otel) method.
+    #[derive(Debug)]
+    pub struct TimedOtel<'a, Fut> where Fut: Future {
+        name: &'a str,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedOtel<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, name: &'a str) -> Self {
+        Self {
+            name,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedOtel<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+
+        if result.is_ready() {
+            let context = opentelemetry::Context::current();
+            let span = context.span();
+
+            // Without an active span, this is a no-op span discarding everything
+            if span.is_recording() {
+                let name = *this.name;
+                let busy = nanos(this.recorder.timing.busy);
+                let idle = nanos(this.recorder.timing.idle);
+
+                span.set_attributes([
+                    KeyValue::new(format!("{name}.busy_ns"), busy),
+                    KeyValue::new(format!("{name}.idle_ns"), idle),
+                ]);
+
+                span.add_event(
+                    name.to_owned(),
+                    vec![
+                        KeyValue::new("busy_ns", busy),
+                        KeyValue::new("idle_ns", idle),
+                    ],
+                );
+            }
+        }
+
+        result
+    }
+}
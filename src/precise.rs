@@ -0,0 +1,253 @@
+//! Timed future splitting idle time into waiting and scheduling delay.
+//!
+//! The idle time reported by [`timed`](super::timed) is the time between polls. It includes both
+//! the time the future was genuinely waiting for an event and the time between being woken and
+//! being polled again, during which it was ready but queued by the executor. To tell them apart,
+//! [`timed_precise_idle`](super::TimedFutureExt::timed_precise_idle) passes a wrapped waker to
+//! the inner future which timestamps the first wake after each poll.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Idle time of a future split at the moments it was woken.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PreciseIdle {
+    /// Time from the end of a poll until the future was woken.
+    pub waiting: Duration,
+    /// Time from being woken until the next poll started.
+    pub scheduling_delay: Duration,
+}
+
+/// Shared between the instrumented future and all clones of the waker passed to it.
+#[derive(Debug, Default)]
+struct WakeState {
+    /// Waker of the most recent poll.
+    waker: Mutex<Option<Waker>>,
+    /// Time of the first wake since the last poll started.
+    woken_at: Mutex<Option<Instant>>,
+}
+
+impl Wake for WakeState {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(clock::now);
+
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Definition of idle time used by
+/// [`timed_idle_definition`](super::TimedFutureExt::timed_idle_definition).
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum IdleKind {
+    /// All time between polls, including the time between being woken and being polled again.
+    /// This is the definition used by all other combinators.
+    #[default]
+    BetweenPolls,
+    /// Only the time from the end of a poll until the future was woken, i.e.
+    /// [`PreciseIdle::waiting`]. The scheduling delay is neither idle nor busy time.
+    Waiting,
+}
+
+/// Recorder polling with a wrapped waker to split idle time at wakes.
+struct PreciseRecorder {
+    recorder: Recorder,
+    state: Arc<WakeState>,
+    waker: Waker,
+    idle: PreciseIdle,
+}
+
+impl PreciseRecorder {
+    fn new() -> Self {
+        let state = Arc::new(WakeState::default());
+
+        Self {
+            recorder: Recorder::new(),
+            waker: Waker::from(state.clone()),
+            state,
+            idle: PreciseIdle::default(),
+        }
+    }
+
+    /// Poll `inner` with the wrapped waker and attribute the time before this poll.
+    fn poll<Fut: Future>(
+        &mut self,
+        inner: Pin<&mut Fut>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Fut::Output> {
+        {
+            let mut waker = self
+                .state
+                .waker
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            if !waker.as_ref().is_some_and(|w| w.will_wake(cx.waker())) {
+                *waker = Some(cx.waker().clone());
+            }
+        }
+
+        let woken_at = self
+            .state
+            .woken_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let previous_end = self.recorder.last_poll_end();
+
+        let mut inner_cx = Context::from_waker(&self.waker);
+        let result = self.recorder.poll(inner, &mut inner_cx);
+
+        if let Some(((start, _), previous_end)) = self.recorder.last_poll().zip(previous_end) {
+            match woken_at {
+                // Woken while idle: waiting until then, queued afterwards.
+                Some(woken_at) if woken_at > previous_end => {
+                    self.idle.waiting += woken_at - previous_end;
+                    self.idle.scheduling_delay += start.saturating_duration_since(woken_at);
+                }
+                // Woken during the previous poll: ready during the entire gap.
+                Some(_) => self.idle.scheduling_delay += start - previous_end,
+                // Polled without a wake, e.g. by a combinator polling all its children.
+                None => self.idle.waiting += start - previous_end,
+            }
+        }
+
+        result
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_precise_idle`](super::TimedFutureExt::timed_precise_idle) method.
+    pub struct TimedPreciseIdle<Fut, F> where Fut: Future, F: FnOnce(Timing, PreciseIdle) {
+        recorder: PreciseRecorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedPreciseIdle<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, PreciseIdle),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: PreciseRecorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedPreciseIdle<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, PreciseIdle),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    op(this.recorder.recorder.timing, this.recorder.idle);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_idle_definition`](super::TimedFutureExt::timed_idle_definition)
+    /// method.
+    pub struct TimedIdleDefinition<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        kind: IdleKind,
+        recorder: PreciseRecorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedIdleDefinition<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, kind: IdleKind, op: F) -> Self {
+        Self {
+            kind,
+            recorder: PreciseRecorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedIdleDefinition<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        // Only pay for the wrapped waker if wakes matter
+        let result = match this.kind {
+            IdleKind::BetweenPolls => this.recorder.recorder.poll(this.inner, cx),
+            IdleKind::Waiting => this.recorder.poll(this.inner, cx),
+        };
+
+        match result {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let mut timing = this.recorder.recorder.timing;
+                if *this.kind == IdleKind::Waiting {
+                    timing.idle = this.recorder.idle.waiting;
+                }
+                if let Some(op) = this.op.take() {
+                    op(timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
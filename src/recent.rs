@@ -0,0 +1,166 @@
+//! Ring buffer of the timing of the most recently completed futures.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::{Timing, TimingAccumulator};
+
+/// Bounded buffer of the timing of the most recently completed futures.
+///
+/// Once `capacity` timings are stored, each new timing overwrites the oldest one. This gives a
+/// view of recent activity with bounded memory, e.g. for debug endpoints dumping the last 100
+/// request timings, without the bucketing of a [`TimingHistogram`](super::TimingHistogram). The
+/// buffer is protected by a mutex, so it can be shared by futures running on different threads.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{RecentTimings, TimedFutureExt};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let recent = RecentTimings::new(2);
+///
+/// for n in 0..3 {
+///     async move { n * 2 }.timed_recent(&recent).await;
+/// }
+///
+/// # if !cfg!(feature = "disabled") {
+/// assert_eq!(recent.iter().count(), 2);
+///
+/// let (mean, max) = recent.stats().unwrap();
+/// assert!(mean.busy <= max.busy);
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecentTimings {
+    capacity: usize,
+    timings: Mutex<VecDeque<Timing>>,
+}
+
+impl RecentTimings {
+    /// Create an empty buffer holding at most `capacity` timings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must not be zero");
+
+        Self {
+            capacity,
+            timings: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Add `timing`, overwriting the oldest timing if the buffer is full.
+    pub fn record(&self, timing: Timing) {
+        let mut timings = self.lock();
+
+        if timings.len() == self.capacity {
+            timings.pop_front();
+        }
+
+        timings.push_back(timing);
+    }
+
+    /// Iterate over a snapshot of the stored timings from oldest to newest.
+    #[must_use]
+    pub fn iter(&self) -> std::vec::IntoIter<Timing> {
+        self.lock().iter().copied().collect::<Vec<_>>().into_iter()
+    }
+
+    /// Mean and element-wise maximum of the stored timings, or `None` if the buffer is empty.
+    #[must_use]
+    pub fn stats(&self) -> Option<(Timing, Timing)> {
+        let timings = self.lock();
+        let max = timings.iter().copied().reduce(Timing::max)?;
+        let sum: Timing = timings.iter().sum();
+        let count = u32::try_from(timings.len()).unwrap_or(u32::MAX);
+
+        Some((sum.per(count), max))
+    }
+
+    /// Maximum number of stored timings.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Timing>> {
+        self.timings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl IntoIterator for &RecentTimings {
+    type Item = Timing;
+    type IntoIter = std::vec::IntoIter<Timing>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Adds the timing, overwriting the oldest one if the buffer is full.
+impl TimingAccumulator for RecentTimings {
+    fn accumulate(&mut self, timing: Timing) {
+        self.record(timing);
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_recent`](super::TimedFutureExt::timed_recent) method.
+    #[derive(Debug)]
+    pub struct TimedRecent<'a, Fut> where Fut: Future {
+        recorder: Recorder,
+        recent: Option<&'a RecentTimings>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedRecent<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, recent: &'a RecentTimings) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            recent: Some(recent),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedRecent<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(recent) = this.recent.take() {
+                    recent.record(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! Registry aggregating timing by label.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::{Nanos, Timing, TimingHistogram};
+
+/// Histogram buckets used unless configured otherwise, from 10µs to 10s in 1-2-5 steps.
+const DEFAULT_BUCKETS: [Duration; 19] = [
+    Duration::from_micros(10),
+    Duration::from_micros(20),
+    Duration::from_micros(50),
+    Duration::from_micros(100),
+    Duration::from_micros(200),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_millis(2),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(20),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Aggregated timing of all measurements recorded under the same label.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegistryEntry {
+    /// Number of recorded measurements.
+    pub count: u64,
+    /// Summed timing of all recorded measurements.
+    pub timing: Timing,
+}
+
+impl RegistryEntry {
+    /// Mean timing of the recorded measurements, zero if nothing was recorded.
+    #[must_use]
+    pub fn mean(&self) -> Timing {
+        let mean = |total: Duration| Nanos(Nanos::from(total).0 / self.count.max(1)).into();
+
+        Timing {
+            idle: mean(self.timing.idle),
+            busy: mean(self.timing.busy),
+        }
+    }
+}
+
+/// Comparison of two variants of an operation returned by [`TimingRegistry::compare`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VariantComparison {
+    /// Aggregated timing of the baseline variant.
+    pub baseline: RegistryEntry,
+    /// Aggregated timing of the candidate variant.
+    pub candidate: RegistryEntry,
+}
+
+impl VariantComparison {
+    /// Mean busy time of the candidate relative to the baseline, e.g. `0.8` if the candidate is
+    /// busy for 20% less time.
+    ///
+    /// Returns infinity if the mean busy time of the baseline is zero but not that of the
+    /// candidate, and NaN if both are zero.
+    #[must_use]
+    pub fn busy_ratio(&self) -> f64 {
+        self.candidate.mean().busy.as_secs_f64() / self.baseline.mean().busy.as_secs_f64()
+    }
+}
+
+/// Thread-safe registry summing up timing per label.
+///
+/// Labels are usually `&'static str` literals naming an operation. Labels only known at runtime
+/// can be recorded as owned [`String`]s via [`record_owned`](Self::record_owned) or
+/// [`timed_named_owned`](super::TimedFutureExt::timed_named_owned). Each distinct label keeps an
+/// entry alive for the lifetime of the registry, so dynamic labels must be drawn from a small,
+/// bounded set: include an endpoint's route template rather than the request path, never user,
+/// request or session identifiers.
+///
+/// Futures instrumented with [`timed_named_hist`](super::TimedFutureExt::timed_named_hist)
+/// additionally count their busy time in a per-label [`TimingHistogram`], from which
+/// [`percentile`](Self::percentile) estimates latency percentiles. Each histogram takes about
+/// 24 bytes per bucket, by default 20 buckets from 10µs to 10s. To report percentiles of
+/// periodic windows, e.g. for scraping, call [`reset`](Self::reset) after each scrape.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{TimedFutureExt, TimingRegistry};
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let registry = TimingRegistry::new();
+///
+/// async { 42 }.timed(registry.recorder("answer")).await;
+/// async { 43 }.timed(registry.recorder("answer")).await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// assert_eq!(registry.get("answer").unwrap().count, 2);
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct TimingRegistry {
+    entries: Mutex<HashMap<Cow<'static, str>, RegistryEntry>>,
+    histograms: Mutex<HashMap<&'static str, TimingHistogram>>,
+    buckets: Option<Vec<Duration>>,
+}
+
+impl TimingRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty registry whose histograms use the given upper bucket boundaries.
+    ///
+    /// See [`TimingHistogram::with_buckets`] for how the boundaries are interpreted.
+    #[must_use]
+    pub fn with_histogram_buckets(boundaries: &[Duration]) -> Self {
+        Self {
+            buckets: Some(boundaries.to_vec()),
+            ..Self::default()
+        }
+    }
+
+    /// Add `timing` to the entry for `label`.
+    pub fn record(&self, label: &'static str, timing: Timing) {
+        self.record_cow(Cow::Borrowed(label), timing);
+    }
+
+    /// Add `timing` to the entry for the runtime-computed `label`.
+    ///
+    /// The registry only keeps `label` if it is not known yet, otherwise it is dropped. The
+    /// allocation of the label itself is left to the caller and happens for every measurement,
+    /// prefer [`record`](Self::record) for labels known at compile time. See [`TimingRegistry`]
+    /// on how to keep the number of distinct labels bounded.
+    pub fn record_owned(&self, label: String, timing: Timing) {
+        self.record_cow(Cow::Owned(label), timing);
+    }
+
+    /// Return a closure recording under the runtime-computed `label`, suitable to be passed to
+    /// [`timed`](super::TimedFutureExt::timed).
+    pub fn recorder_owned(&self, label: String) -> impl FnOnce(Timing) + '_ {
+        move |timing| self.record_owned(label, timing)
+    }
+
+    fn record_cow(&self, label: Cow<'static, str>, timing: Timing) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(entry) = entries.get_mut(label.as_ref()) {
+            entry.count += 1;
+            entry.timing += timing;
+            return;
+        }
+
+        entries.insert(label, RegistryEntry { count: 1, timing });
+    }
+
+    /// Return a closure recording under `label`, suitable to be passed to
+    /// [`timed`](super::TimedFutureExt::timed).
+    pub fn recorder(&self, label: &'static str) -> impl FnOnce(Timing) + '_ {
+        move |timing| self.record(label, timing)
+    }
+
+    /// Add `timing` to the entry for `label` and count its busy time in the histogram of `label`.
+    pub fn record_with_histogram(&self, label: &'static str, timing: Timing) {
+        self.record(label, timing);
+
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(label)
+            .or_insert_with(|| {
+                TimingHistogram::with_buckets(self.buckets.as_deref().unwrap_or(&DEFAULT_BUCKETS))
+            })
+            .record_timing(timing);
+    }
+
+    /// Estimate the `quantile` of the busy time recorded with a histogram under `label`.
+    ///
+    /// See [`TimingHistogram::percentile`] for the precision of the estimate. Returns `None` if
+    /// nothing was recorded with a histogram under `label`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingRegistry};
+    /// use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let registry = TimingRegistry::new();
+    ///
+    /// for _ in 0..100 {
+    ///     async { 42 }.timed_named_hist("answer", &registry).await;
+    /// }
+    ///
+    /// # if !cfg!(feature = "disabled") {
+    /// let p99 = registry.percentile("answer", 0.99).unwrap();
+    /// assert!(p99 <= Duration::from_millis(1));
+    /// # }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn percentile(&self, label: &str, quantile: f64) -> Option<Duration> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(label)?
+            .percentile(quantile)
+    }
+
+    /// Copy of the histogram of `label` if anything was recorded with a histogram under it.
+    #[must_use]
+    pub fn histogram(&self, label: &str) -> Option<TimingHistogram> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(label)
+            .cloned()
+    }
+
+    /// Remove all entries and histograms, starting a new window.
+    pub fn reset(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Aggregated timing for `label` if anything was recorded under it.
+    #[must_use]
+    pub fn get(&self, label: &str) -> Option<RegistryEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(label)
+            .copied()
+    }
+
+    /// Compare the aggregated timing of the `baseline` and `candidate` variants, usually recorded
+    /// with [`timed_variant`](super::TimedFutureExt::timed_variant).
+    ///
+    /// Returns `None` unless both variants were recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{TimedFutureExt, TimingRegistry};
+    /// # use std::time::{Duration, Instant};
+    /// # fn spin(duration: Duration) {
+    /// #     let start = Instant::now();
+    /// #     while start.elapsed() < duration {}
+    /// # }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let registry = TimingRegistry::new();
+    ///
+    /// for request in 0..100 {
+    ///     if request % 2 == 0 {
+    ///         async { spin(Duration::from_micros(400)) }
+    ///             .timed_variant("parser/v1", &registry)
+    ///             .await;
+    ///     } else {
+    ///         async { spin(Duration::from_micros(100)) }
+    ///             .timed_variant("parser/v2", &registry)
+    ///             .await;
+    ///     }
+    /// }
+    ///
+    /// # if !cfg!(feature = "disabled") {
+    /// let comparison = registry.compare("parser/v1", "parser/v2").unwrap();
+    ///
+    /// println!(
+    ///     "v2 is busy for {:.0}% of v1, p99 {:?} vs {:?}",
+    ///     comparison.busy_ratio() * 100.0,
+    ///     registry.percentile("parser/v2", 0.99).unwrap(),
+    ///     registry.percentile("parser/v1", 0.99).unwrap(),
+    /// );
+    /// assert!(comparison.busy_ratio() < 1.0);
+    /// # }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn compare(&self, baseline: &str, candidate: &str) -> Option<VariantComparison> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        Some(VariantComparison {
+            baseline: *entries.get(baseline)?,
+            candidate: *entries.get(candidate)?,
+        })
+    }
+
+    /// All labels and their aggregated timing, sorted by label.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<(Cow<'static, str>, RegistryEntry)> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut snapshot = entries
+            .iter()
+            .map(|(label, entry)| (label.clone(), *entry))
+            .collect::<Vec<_>>();
+        snapshot.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        snapshot
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_named_owned`](super::TimedFutureExt::timed_named_owned) method.
+    #[derive(Debug)]
+    pub struct TimedNamedOwned<'a, Fut> where Fut: Future {
+        recorder: Recorder,
+        label: Option<String>,
+        registry: &'a TimingRegistry,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedNamedOwned<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, label: String, registry: &'a TimingRegistry) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            label: Some(label),
+            registry,
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedNamedOwned<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(label) = this.label.take() {
+                    this.registry.record_owned(label, this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_named_hist`](super::TimedFutureExt::timed_named_hist) method.
+    #[derive(Debug)]
+    pub struct TimedNamedHist<'a, Fut> where Fut: Future {
+        recorder: Recorder,
+        label: &'static str,
+        registry: Option<&'a TimingRegistry>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedNamedHist<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, label: &'static str, registry: &'a TimingRegistry) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            label,
+            registry: Some(registry),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedNamedHist<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(registry) = this.registry.take() {
+                    registry.record_with_histogram(this.label, this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
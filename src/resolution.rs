@@ -0,0 +1,66 @@
+//! Timed future skipping reports of busy times below the clock resolution.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::clock::clock_resolution;
+use crate::timed::Recorder;
+use crate::Timing;
+
+pin_project! {
+    /// Future for the [`timed_above_resolution`](super::TimedFutureExt::timed_above_resolution)
+    /// method.
+    pub struct TimedAboveResolution<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedAboveResolution<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedAboveResolution<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let timing = this.recorder.timing;
+
+                if timing.busy >= clock_resolution() {
+                    if let Some(op) = this.op.take() {
+                        op(timing);
+                    }
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
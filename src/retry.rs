@@ -0,0 +1,87 @@
+//! Accumulator summing the timing of multiple attempts of a retried operation.
+
+use std::sync::{Mutex, PoisonError};
+
+use crate::Timing;
+
+/// Accumulates the timing of all attempts of a retried operation.
+///
+/// Timing each attempt individually obscures the true cost of an operation that had to be retried.
+/// Feed the timing of each attempt into a shared `RetryTiming` using [`RetryTiming::recorder`] and
+/// report the summed busy and idle time together with the number of attempts once the operation
+/// finally succeeded or gave up.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{RetryTiming, TimedFutureExt};
+/// # use std::time::Duration;
+/// # async fn fallible(attempt: u32) -> Result<u64, ()> {
+/// #   tokio::time::sleep(Duration::from_micros(10)).await;
+/// #   if attempt < 2 { Err(()) } else { Ok(42) }
+/// # }
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let retries = RetryTiming::new();
+/// let mut attempt = 0;
+///
+/// let output = loop {
+///     match fallible(attempt).timed(retries.recorder()).await {
+///         Ok(output) => break output,
+///         Err(()) => attempt += 1,
+///     }
+/// };
+///
+/// assert_eq!(output, 42);
+/// # if !cfg!(feature = "disabled") {
+/// assert_eq!(retries.attempts(), 3);
+/// assert!(!retries.timing().idle.is_zero());
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RetryTiming {
+    state: Mutex<(Timing, u32)>,
+}
+
+impl RetryTiming {
+    /// Create an empty accumulator.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new((Timing::ZERO, 0)),
+        }
+    }
+
+    /// Add the timing of a single attempt.
+    pub fn record(&self, timing: Timing) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.0 += timing;
+        state.1 += 1;
+    }
+
+    /// Return a closure recording the timing of a single attempt, suitable to be passed to
+    /// [`timed`](super::TimedFutureExt::timed).
+    pub fn recorder(&self) -> impl FnOnce(Timing) + '_ {
+        move |timing| self.record(timing)
+    }
+
+    /// Number of attempts recorded so far.
+    #[must_use]
+    pub fn attempts(&self) -> u32 {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).1
+    }
+
+    /// Summed timing across all attempts recorded so far.
+    #[must_use]
+    pub fn timing(&self) -> Timing {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).0
+    }
+}
+
+impl Default for RetryTiming {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,132 @@
+//! Guard timing a section inside an async function.
+//!
+//! Futures instrumented with [`timed`](super::TimedFutureExt::timed) publish their accumulated
+//! busy time in a thread-local while their inner future is polled. A guard created inside such a
+//! future can thus compute how much busy time elapsed between its creation and its drop, even
+//! across `.await` points.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::clock::{self, Stopwatch};
+use crate::{Timing, TimingRegistry};
+
+thread_local! {
+    static CURRENT: Cell<Option<Poll>> = const { Cell::new(None) };
+}
+
+/// Busy time of the innermost instrumented future currently being polled.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Poll {
+    busy: Duration,
+    stopwatch: Stopwatch,
+}
+
+/// Publish the busy time of a future about to be polled and the stopwatch measuring the poll,
+/// returning the previously published state which must be restored with [`leave`] once the poll
+/// is done.
+pub(crate) fn enter(busy: Duration, stopwatch: Stopwatch) -> Option<Poll> {
+    CURRENT.with(|current| current.replace(Some(Poll { busy, stopwatch })))
+}
+
+/// Restore the state returned by [`enter`].
+pub(crate) fn leave(previous: Option<Poll>) {
+    CURRENT.with(|current| current.set(previous));
+}
+
+/// Busy time accumulated by the innermost instrumented future up to now.
+pub(crate) fn busy_now() -> Option<Duration> {
+    CURRENT
+        .with(Cell::get)
+        .map(|poll| poll.busy.saturating_add(poll.stopwatch.stop().1))
+}
+
+/// Start timing a labeled section of an async function.
+///
+/// Call [`TimingScope::await_guard`] to start measuring. The busy time between that call and
+/// dropping the returned [`TimingGuard`] is recorded into `registry` under `label`. This is
+/// only possible if the section runs inside a future instrumented with
+/// [`timed`](super::TimedFutureExt::timed) or
+/// [`timed_collect`](super::TimedFutureExt::timed_collect), the only combinators tracking
+/// sections so that all others stay cheap. Otherwise, there is no way to tell apart time spent
+/// suspended at `.await` points and the elapsed wall-clock time is recorded as busy time instead.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{timing_scope, TimedFutureExt, TimingRegistry};
+/// # use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let registry = TimingRegistry::new();
+///
+/// async {
+///     let _guard = timing_scope("parse", &registry).await_guard();
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+/// }
+/// .timed(|_| {})
+/// .await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// let parse = registry.get("parse").unwrap();
+/// assert!(parse.timing.busy < Duration::from_millis(5));
+/// # }
+/// # }
+/// ```
+pub fn timing_scope<'a>(label: &'static str, registry: &'a TimingRegistry) -> TimingScope<'a> {
+    TimingScope { label, registry }
+}
+
+/// Labeled section created by [`timing_scope`].
+#[derive(Debug)]
+pub struct TimingScope<'a> {
+    label: &'static str,
+    registry: &'a TimingRegistry,
+}
+
+impl<'a> TimingScope<'a> {
+    /// Start measuring until the returned guard is dropped.
+    ///
+    /// With the `disabled` feature, nothing is measured or recorded.
+    #[must_use = "the section ends when the guard is dropped"]
+    pub fn await_guard(self) -> TimingGuard<'a> {
+        let start = (!cfg!(feature = "disabled")).then(clock::now);
+
+        TimingGuard {
+            scope: self,
+            start,
+            busy: start.and_then(|_| busy_now()),
+        }
+    }
+}
+
+/// Guard recording the busy time of a section when dropped.
+#[derive(Debug)]
+pub struct TimingGuard<'a> {
+    scope: TimingScope<'a>,
+    // `None` with the `disabled` feature.
+    start: Option<Instant>,
+    busy: Option<Duration>,
+}
+
+impl Drop for TimingGuard<'_> {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+
+        let elapsed = clock::now().saturating_duration_since(start);
+        let busy = match (self.busy, busy_now()) {
+            (Some(start), Some(end)) => end.saturating_sub(start),
+            _ => elapsed,
+        };
+
+        let timing = Timing {
+            idle: elapsed.saturating_sub(busy),
+            busy,
+        };
+
+        self.scope.registry.record(self.scope.label, timing);
+    }
+}
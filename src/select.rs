@@ -0,0 +1,153 @@
+//! Racing two futures while timing the winner and the cancelled loser.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Output of [`timed_select`] telling which future completed first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selected<L, R> {
+    /// The left future completed first with this output.
+    Left(L),
+    /// The right future completed first with this output.
+    Right(R),
+}
+
+/// Timing of both futures raced by [`timed_select`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SelectTiming {
+    /// Timing of the future that completed first.
+    pub winner: Timing,
+    /// Partial timing of the future that was cancelled, i.e. its wasted work.
+    pub loser: Timing,
+}
+
+/// Race two futures and report the timing of both once the first one completes.
+///
+/// The future completing first wins, the other one is dropped right away. The closure receives the
+/// timing of the winner along with the partial timing of the loser, which makes work wasted in
+/// cancelled branches of timeouts or races visible. The left future is polled first, so if both
+/// complete in the same poll, the left one wins. If the returned future itself is dropped before
+/// either completes, nothing is reported.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{timed_select, Selected};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let request = async {
+///     tokio::time::sleep(Duration::from_secs(10)).await;
+///     42
+/// };
+/// let timeout = tokio::time::sleep(Duration::from_millis(1));
+///
+/// let output = timed_select(request, timeout, |timing| {
+///     println!("timeout after {timing:?}");
+/// })
+/// .await;
+///
+/// assert_eq!(output, Selected::Right(()));
+/// # }
+pub fn timed_select<A, B, F>(left: A, right: B, f: F) -> TimedSelect<A, B, F>
+where
+    A: Future,
+    B: Future,
+    F: FnOnce(SelectTiming),
+{
+    TimedSelect::new(left, right, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_select`] function.
+    pub struct TimedSelect<A, B, F> where A: Future, B: Future, F: FnOnce(SelectTiming) {
+        left_recorder: Recorder,
+        right_recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        left: Option<A>,
+        #[pin]
+        right: Option<B>,
+    }
+}
+
+impl<A, B, F> TimedSelect<A, B, F>
+where
+    A: Future,
+    B: Future,
+    F: FnOnce(SelectTiming),
+{
+    pub(crate) fn new(left: A, right: B, op: F) -> Self {
+        Self {
+            left_recorder: Recorder::new(),
+            right_recorder: Recorder::new(),
+            op: Some(op),
+            left: Some(left),
+            right: Some(right),
+        }
+    }
+}
+
+impl<A, B, F> Future for TimedSelect<A, B, F>
+where
+    A: Future,
+    B: Future,
+    F: FnOnce(SelectTiming),
+{
+    type Output = Selected<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        let disabled = cfg!(feature = "disabled");
+
+        let (Some(left), Some(right)) = (
+            this.left.as_mut().as_pin_mut(),
+            this.right.as_mut().as_pin_mut(),
+        ) else {
+            panic!("`TimedSelect` polled after completion");
+        };
+
+        let left = if disabled {
+            left.poll(cx)
+        } else {
+            this.left_recorder.poll(left, cx)
+        };
+
+        let selected = match left {
+            Poll::Ready(output) => Selected::Left(output),
+            Poll::Pending => {
+                let right = if disabled {
+                    right.poll(cx)
+                } else {
+                    this.right_recorder.poll(right, cx)
+                };
+
+                match right {
+                    Poll::Ready(output) => Selected::Right(output),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+        };
+
+        this.left.set(None);
+        this.right.set(None);
+
+        if let Some(op) = this.op.take().filter(|_| !disabled) {
+            let (winner, loser) = match selected {
+                Selected::Left(_) => (this.left_recorder.timing, this.right_recorder.timing),
+                Selected::Right(_) => (this.right_recorder.timing, this.left_recorder.timing),
+            };
+
+            op(SelectTiming { winner, loser });
+        }
+
+        Poll::Ready(selected)
+    }
+}
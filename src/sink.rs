@@ -0,0 +1,106 @@
+//! Timed future reporting its timing to a dynamically dispatched sink.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Destination for timing reports chosen at runtime.
+///
+/// Unlike the closures passed to [`timed`](super::timed), a sink is used as a trait object, so the
+/// concrete reporting type does not show up in the type of the instrumented future. This suits
+/// applications which select loggers or metrics backends at runtime.
+pub trait TimingSink: Send + Sync {
+    /// Report the timing of a completed future.
+    fn report(&self, timing: Timing);
+}
+
+/// Sink discarding all reports.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopSink;
+
+impl TimingSink for NoopSink {
+    fn report(&self, _: Timing) {}
+}
+
+/// Sink calling a closure for each report.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{FnSink, TimingSink};
+/// use std::sync::Arc;
+///
+/// let sink: Arc<dyn TimingSink> = Arc::new(FnSink(|timing| println!("{timing:?}")));
+/// ```
+#[derive(Clone, Copy)]
+pub struct FnSink<F>(pub F);
+
+impl<F> TimingSink for FnSink<F>
+where
+    F: Fn(Timing) + Send + Sync,
+{
+    fn report(&self, timing: Timing) {
+        (self.0)(timing);
+    }
+}
+
+impl<F> fmt::Debug for FnSink<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnSink").finish_non_exhaustive()
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_sink`](super::TimedFutureExt::timed_sink) method.
+    pub struct TimedSink<Fut> where Fut: Future {
+        recorder: Recorder,
+        sink: Option<Arc<dyn TimingSink>>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedSink<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, sink: Arc<dyn TimingSink>) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            sink: Some(sink),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedSink<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(sink) = this.sink.take() {
+                    sink.report(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Timed future calling a closure on completion only if it was slow.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Instrument a future to report its timing only if the total time exceeded `threshold`.
+///
+/// The timing is always measured, but the closure is called once on completion only if the sum of
+/// busy and idle time is greater than `threshold`. Unlike [`warn_if`](super::warn_if), which
+/// looks at each individual poll, this reports the full [`Timing`] of slow futures and is meant
+/// to log only the slow tail of requests. In general, it is more straightforward to use the
+/// [`super::TimedFutureExt`] extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_if_slower_than;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let output = timed_if_slower_than(async { 42 }, Duration::from_secs(1), |_| {
+///     unreachable!("future was fast");
+/// })
+/// .await;
+///
+/// assert_eq!(output, 42);
+/// # }
+pub fn timed_if_slower_than<Fut, F>(
+    fut: Fut,
+    threshold: Duration,
+    f: F,
+) -> TimedIfSlowerThan<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    TimedIfSlowerThan::new(fut, threshold, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_if_slower_than`] function and
+    /// [`timed_if_slower_than`](super::TimedFutureExt::timed_if_slower_than) method.
+    pub struct TimedIfSlowerThan<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        threshold: Duration,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedIfSlowerThan<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, op: F) -> Self {
+        Self {
+            threshold,
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedIfSlowerThan<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                let timing = this.recorder.timing;
+
+                if timing.exceeds_total(*this.threshold) {
+                    if let Some(op) = this.op.take() {
+                        op(timing);
+                    }
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Timed future logging slow polls with `defmt`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+
+pin_project! {
+    /// Future for the [`warn_slow_defmt`](super::TimedFutureExt::warn_slow_defmt) method.
+    #[derive(Debug)]
+    pub struct WarnSlowDefmt<Fut> where Fut: Future {
+        threshold: Duration,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> WarnSlowDefmt<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration) -> Self {
+        Self { threshold, inner }
+    }
+}
+
+impl<Fut> Future for WarnSlowDefmt<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let start = clock::now();
+        let mut this = self.project();
+        let result = this.inner.as_mut().poll(cx);
+        let end = clock::now();
+
+        let busy = end - start;
+
+        if busy >= *this.threshold {
+            let micros = u64::try_from(busy.as_micros()).unwrap_or(u64::MAX);
+            defmt::warn!("poll took {=u64} us", micros);
+        }
+
+        result
+    }
+}
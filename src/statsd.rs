@@ -0,0 +1,73 @@
+//! Timed future sending its busy time to a Statsd server on completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use cadence::{StatsdClient, Timed as _};
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+
+pin_project! {
+    /// Future for the [`timed_statsd`](super::TimedFutureExt::timed_statsd) and
+    /// [`timed_statsd_tagged`](super::TimedFutureExt::timed_statsd_tagged) methods.
+    #[derive(Debug)]
+    pub struct TimedStatsd<'a, Fut> where Fut: Future {
+        client: &'a StatsdClient,
+        metric_name: &'a str,
+        tags: &'a [(&'a str, &'a str)],
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedStatsd<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(
+        inner: Fut,
+        client: &'a StatsdClient,
+        metric_name: &'a str,
+        tags: &'a [(&'a str, &'a str)],
+    ) -> Self {
+        Self {
+            client,
+            metric_name,
+            tags,
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedStatsd<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = this.recorder.poll(this.inner, cx);
+
+        if result.is_ready() {
+            let metric = this
+                .client
+                .time_with_tags(this.metric_name, this.recorder.timing.busy);
+
+            this.tags
+                .iter()
+                .fold(metric, |metric, (key, value)| metric.with_tag(key, value))
+                .send();
+        }
+
+        result
+    }
+}
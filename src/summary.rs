@@ -0,0 +1,139 @@
+//! Descriptive statistics of a collection of timings.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::Timing;
+
+/// Statistics of a collection of timings computed by [`summarize`].
+///
+/// Like [`Timing::min`] and [`Timing::max`], all statistics are computed separately for the busy
+/// and the idle time, so a single [`Timing`] field does not necessarily correspond to a single
+/// measurement. The [`Display`](fmt::Display) implementation renders the statistics as a table.
+#[derive(Clone, Copy, Debug, Hash, PartialEq)]
+pub struct TimingSummary {
+    /// Number of timings.
+    pub count: usize,
+    /// Sum of all timings.
+    pub total: Timing,
+    /// Arithmetic mean of all timings.
+    pub mean: Timing,
+    /// Smallest busy and idle time.
+    pub min: Timing,
+    /// Largest busy and idle time.
+    pub max: Timing,
+    /// Median busy and idle time.
+    pub p50: Timing,
+    /// 90th percentile of the busy and idle time.
+    pub p90: Timing,
+    /// 99th percentile of the busy and idle time.
+    pub p99: Timing,
+}
+
+/// Compute count, total, mean, minimum, maximum and percentiles of `timings`.
+///
+/// Percentiles are exact and use the nearest-rank method, i.e. the p-th percentile is the
+/// smallest measured value such that at least p percent of the measurements are less than or
+/// equal to it. For an empty slice, all statistics are zero.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{summarize, Timing};
+/// use std::time::Duration;
+///
+/// let ms = Duration::from_millis;
+/// let timings: Vec<Timing> = (1..=10)
+///     .map(|i| Timing::builder().busy(ms(i)).idle(ms(2 * i)).build())
+///     .collect();
+///
+/// let summary = summarize(&timings);
+///
+/// assert_eq!(summary.count, 10);
+/// assert_eq!(summary.p50.busy, ms(5));
+/// assert_eq!(summary.max.idle, ms(20));
+///
+/// // Prints a table with one row for busy and one for idle time
+/// println!("{summary}");
+/// ```
+#[must_use]
+pub fn summarize(timings: &[Timing]) -> TimingSummary {
+    let Some(first) = timings.first().copied() else {
+        return TimingSummary {
+            count: 0,
+            total: Timing::ZERO,
+            mean: Timing::ZERO,
+            min: Timing::ZERO,
+            max: Timing::ZERO,
+            p50: Timing::ZERO,
+            p90: Timing::ZERO,
+            p99: Timing::ZERO,
+        };
+    };
+
+    let total: Timing = timings.iter().sum();
+    let count = u32::try_from(timings.len()).unwrap_or(u32::MAX);
+
+    let mut busy: Vec<Duration> = timings.iter().map(|timing| timing.busy).collect();
+    let mut idle: Vec<Duration> = timings.iter().map(|timing| timing.idle).collect();
+    busy.sort_unstable();
+    idle.sort_unstable();
+
+    let percentile = |quantile: f64| Timing {
+        idle: nearest_rank(&idle, quantile),
+        busy: nearest_rank(&busy, quantile),
+    };
+
+    TimingSummary {
+        count: timings.len(),
+        total,
+        mean: total.per(count),
+        min: timings.iter().copied().fold(first, Timing::min),
+        max: timings.iter().copied().fold(first, Timing::max),
+        p50: percentile(0.5),
+        p90: percentile(0.9),
+        p99: percentile(0.99),
+    }
+}
+
+/// Nearest-rank percentile of the non-empty, sorted `values`.
+// Slices beyond 2^53 elements lose precision as `f64`, which is irrelevant for a percentile.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn nearest_rank(values: &[Duration], quantile: f64) -> Duration {
+    let rank = (quantile * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+impl fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "count: {}", self.count)?;
+        writeln!(
+            f,
+            "{:<6}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}",
+            "", "total", "mean", "min", "p50", "p90", "p99", "max"
+        )?;
+
+        let columns = [
+            self.total, self.mean, self.min, self.p50, self.p90, self.p99, self.max,
+        ];
+
+        for (name, column) in [
+            ("busy", columns.map(|timing| timing.busy)),
+            ("idle", columns.map(|timing| timing.idle)),
+        ] {
+            write!(f, "{name:<6}")?;
+
+            for duration in column {
+                write!(f, "{:>12}", format!("{duration:.1?}"))?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
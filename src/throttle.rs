@@ -0,0 +1,71 @@
+//! Future delaying its next poll after a poll was busy for too long.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+use tokio::time::Sleep;
+
+use crate::clock;
+
+pin_project! {
+    /// Future for the [`throttle_if_busy`](super::TimedFutureExt::throttle_if_busy) method.
+    #[derive(Debug)]
+    pub struct ThrottleIfBusy<Fut> where Fut: Future {
+        max_busy_per_poll: Duration,
+        delay: Duration,
+        #[pin]
+        sleep: Option<Sleep>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> ThrottleIfBusy<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, max_busy_per_poll: Duration, delay: Duration) -> Self {
+        Self {
+            max_busy_per_poll,
+            delay,
+            sleep: None,
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for ThrottleIfBusy<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let mut this = self.project();
+
+        if let Some(sleep) = this.sleep.as_mut().as_pin_mut() {
+            if sleep.poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            this.sleep.set(None);
+        }
+
+        let start = clock::now();
+        let result = this.inner.poll(cx);
+        let busy = clock::now() - start;
+
+        if result.is_pending() && busy > *this.max_busy_per_poll {
+            this.sleep.set(Some(tokio::time::sleep(*this.delay)));
+        }
+
+        result
+    }
+}
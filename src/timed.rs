@@ -1,12 +1,17 @@
 //! Timed future calling a closure on completion.
 
-use std::future::Future;
+use std::future::{poll_fn, Future, PollFn};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, DerefMut};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use pin_project_lite::pin_project;
 
+use crate::clock::Stopwatch;
+use crate::scope;
+
 /// Instrument a future to record its timing.
 ///
 /// The busy and idle time for the future will be passed as an argument to the provided closure.
@@ -41,35 +46,224 @@ where
     Timed::new(fut, f)
 }
 
+/// Create a future from a poll function and instrument it to record its timing.
+///
+/// This is a shorthand for calling [`timed`] on [`std::future::poll_fn`]. Each poll returning
+/// [`Poll::Pending`] ends a busy interval and starts an idle interval which lasts until the
+/// function is polled again.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{timed_poll_fn, Timing};
+/// use std::task::Poll;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let mut pending = true;
+///
+/// let output = timed_poll_fn(
+///     |cx| {
+///         if std::mem::take(&mut pending) {
+///             cx.waker().wake_by_ref();
+///             Poll::Pending
+///         } else {
+///             Poll::Ready(42)
+///         }
+///     },
+///     |Timing { idle, busy }| {
+///         assert!(!idle.is_zero());
+///         assert!(!busy.is_zero());
+///     },
+/// )
+/// .await;
+///
+/// assert_eq!(output, 42);
+/// # }
+pub fn timed_poll_fn<T, P, F>(p: P, f: F) -> Timed<PollFn<P>, F>
+where
+    P: FnMut(&mut Context<'_>) -> Poll<T>,
+    F: FnOnce(Timing),
+{
+    Timed::new(poll_fn(p), f)
+}
+
+/// Instrument a future to return its timing together with its output.
+///
+/// This is the closure-free primitive underlying [`timed`]: the caller decides what to do with
+/// the timing after the fact, e.g. when the reporting target is only known on completion.
+/// `timed(fut, f)` behaves like awaiting `timed_collect(fut)` and passing the timing to `f`. In
+/// general, it is more straightforward to use the [`super::TimedFutureExt`] extension trait to
+/// instrument a future directly.
+///
+/// With the `disabled` feature, the returned timing is always zero.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::timed_collect;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let (output, timing) = timed_collect(async { 42 }).await;
+///
+/// assert_eq!(output, 42);
+/// assert!(timing.idle.is_zero());
+/// # }
+pub fn timed_collect<Fut>(fut: Fut) -> TimedCollect<Fut>
+where
+    Fut: Future,
+{
+    TimedCollect::new(fut)
+}
+
+pin_project! {
+    /// Future for the [`timed_collect`] function and
+    /// [`timed_collect`](TimedFutureExt::timed_collect) method.
+    #[derive(Debug)]
+    pub struct TimedCollect<Fut> where Fut: Future {
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedCollect<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedCollect<Fut>
+where
+    Fut: Future,
+{
+    type Output = (Fut::Output, Timing);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if cfg!(feature = "disabled") {
+            return this
+                .inner
+                .poll(cx)
+                .map(|output| (output, this.recorder.timing));
+        }
+
+        this.recorder
+            .poll_scoped(this.inner, cx)
+            .map(|output| (output, this.recorder.timing))
+    }
+}
+
 pin_project! {
     /// Future for the [`timed`] function and [`timed`](TimedFutureExt::timed) method.
     pub struct Timed<Fut, F> where Fut: Future, F: FnOnce(Timing) {
-        last_poll_end: Option<Instant>,
-        timing: Timing,
         op: Option<F>,
         #[pin]
-        inner: Fut,
+        inner: TimedCollect<Fut>,
     }
 }
 
+/// [`Timed`] future with a boxed closure, returned by the
+/// [`timed_boxed_closure`](super::TimedFutureExt::timed_boxed_closure) method.
+///
+/// Unlike the closure type, the type of the boxed closure can be named, so that an instrumented
+/// future can be stored in a struct field. If the inner future cannot be named either, box it as
+/// well, e.g. as `BoxTimed<Pin<Box<dyn Future<Output = T> + Send>>>`.
+pub type BoxTimed<Fut> = Timed<Fut, Box<dyn FnOnce(Timing) + Send>>;
+
 impl<Fut, F> Timed<Fut, F>
 where
     Fut: Future,
     F: FnOnce(Timing),
 {
     pub(crate) fn new(inner: Fut, op: F) -> Self {
-        let timing = Timing {
-            idle: Duration::ZERO,
-            busy: Duration::ZERO,
-        };
-
         Self {
-            last_poll_end: None,
-            timing,
             op: Some(op),
-            inner,
+            inner: TimedCollect::new(inner),
         }
     }
+
+    /// Timing accumulated up to the end of the most recent poll.
+    ///
+    /// This is only meaningful between polls, e.g. for custom drivers inspecting the progress of
+    /// a pinned future. The idle time since the last poll is not included until the future is
+    /// polled again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use std::pin::pin;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut fut = pin!(tokio::task::yield_now().timed(|_| {}));
+    /// assert!(fut.as_ref().timing_so_far().busy.is_zero());
+    ///
+    /// assert!(futures::poll!(fut.as_mut()).is_pending());
+    /// # if !cfg!(feature = "disabled") {
+    /// assert!(!fut.as_ref().timing_so_far().busy.is_zero());
+    /// # }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn timing_so_far(self: Pin<&Self>) -> Timing {
+        self.get_ref().inner.recorder.timing
+    }
+}
+
+impl<P, F> Timed<Pin<P>, F>
+where
+    P: DerefMut,
+    P::Target: Future,
+    F: FnOnce(Timing),
+{
+    /// Instrument a pinned future that may already have been polled, e.g. by an executor or an
+    /// instrumentation layer receiving futures in flight.
+    ///
+    /// Measuring starts with the first poll through the returned future: the busy time of earlier
+    /// polls and the idle time before the first poll after wrapping are not included. The pinned
+    /// pointer is polled in place, so it can be a `Pin<&mut Fut>` borrowed from a framework as
+    /// well as an owned `Pin<Box<Fut>>`. This is equivalent to calling
+    /// [`timed`](super::TimedFutureExt::timed) on the pinned pointer but spells out the intent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timed;
+    /// use std::pin::pin;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut fut = pin!(async {
+    ///     std::thread::sleep(Duration::from_millis(10));
+    ///     tokio::task::yield_now().await;
+    ///     42
+    /// });
+    ///
+    /// // The first poll happens before instrumenting
+    /// assert!(futures::poll!(fut.as_mut()).is_pending());
+    ///
+    /// let output = Timed::wrap_pinned(fut, |timing| {
+    ///     assert!(timing.busy < Duration::from_millis(10));
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(output, 42);
+    /// # }
+    /// ```
+    pub fn wrap_pinned(fut: Pin<P>, op: F) -> Self {
+        Self::new(fut, op)
+    }
 }
 
 impl<Fut, F> Future for Timed<Fut, F>
@@ -80,23 +274,17 @@ where
     type Output = Fut::Output;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let start = Instant::now();
-        let mut this = self.project();
-        let result = this.inner.as_mut().poll(cx);
-        let end = Instant::now();
-
-        if let Some(last_poll_end) = this.last_poll_end.take() {
-            this.timing.idle += start - last_poll_end;
-        }
-
-        this.timing.busy += end - start;
-        *this.last_poll_end = Some(end);
+        let this = self.project();
 
-        match result {
+        match this.inner.poll(cx) {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(output) => {
+            Poll::Ready((output, timing)) => {
+                if cfg!(feature = "disabled") {
+                    return Poll::Ready(output);
+                }
+
                 if let Some(op) = this.op.take() {
-                    op(*this.timing);
+                    op(timing);
                 }
                 Poll::Ready(output)
             }
@@ -104,6 +292,77 @@ where
     }
 }
 
+/// Accumulates the busy and idle time across the polls of a future.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Recorder {
+    last_poll_start: Option<Instant>,
+    last_poll_end: Option<Instant>,
+    pub(crate) timing: Timing,
+}
+
+impl Recorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_poll_start: None,
+            last_poll_end: None,
+            timing: Timing::ZERO,
+        }
+    }
+
+    /// Poll `inner` and add the time spent in and before this poll.
+    pub(crate) fn poll<Fut: Future>(
+        &mut self,
+        inner: Pin<&mut Fut>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Fut::Output> {
+        let stopwatch = Stopwatch::start();
+        let result = inner.poll(cx);
+        self.record(stopwatch);
+        result
+    }
+
+    /// Like [`poll`](Self::poll), but also publish the busy time to [`timing_scope`] sections
+    /// within `inner`.
+    ///
+    /// [`timing_scope`]: crate::timing_scope
+    pub(crate) fn poll_scoped<Fut: Future>(
+        &mut self,
+        inner: Pin<&mut Fut>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Fut::Output> {
+        let stopwatch = Stopwatch::start();
+        let previous = scope::enter(self.timing.busy, stopwatch);
+        let result = inner.poll(cx);
+        scope::leave(previous);
+        self.record(stopwatch);
+        result
+    }
+
+    /// Add the time spent in the poll measured by `stopwatch` and before it, for futures polling
+    /// their inner future themselves.
+    pub(crate) fn record(&mut self, stopwatch: Stopwatch) {
+        let (end, busy) = stopwatch.stop();
+
+        if let Some(last_poll_end) = self.last_poll_end.take() {
+            self.timing.idle += stopwatch.start - last_poll_end;
+        }
+
+        self.timing.busy += busy;
+        self.last_poll_start = Some(stopwatch.start);
+        self.last_poll_end = Some(end);
+    }
+
+    /// End of the most recent poll.
+    pub(crate) fn last_poll_end(&self) -> Option<Instant> {
+        self.last_poll_end
+    }
+
+    /// Wall-clock start and end of the most recent poll.
+    pub(crate) fn last_poll(&self) -> Option<(Instant, Instant)> {
+        self.last_poll_start.zip(self.last_poll_end)
+    }
+}
+
 /// Timing information for an instrumented future.
 #[derive(Clone, Copy, Debug, Hash, PartialEq)]
 pub struct Timing {
@@ -114,3 +373,452 @@ pub struct Timing {
     /// on that future.
     pub busy: Duration,
 }
+
+impl Timing {
+    /// Zero idle and busy time, the additive identity of timings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// const NOTHING: Timing = Timing::ZERO;
+    ///
+    /// let timing = Timing::builder().busy(Duration::from_millis(2)).build();
+    ///
+    /// assert_eq!(timing + NOTHING, timing);
+    /// assert!(NOTHING.busy.is_zero() && NOTHING.idle.is_zero());
+    /// ```
+    pub const ZERO: Self = Self {
+        idle: Duration::ZERO,
+        busy: Duration::ZERO,
+    };
+
+    /// Create a builder for arbitrary timing values.
+    ///
+    /// This is mostly useful in tests of code consuming [`Timing`]. Unlike a struct literal, code
+    /// using the builder keeps compiling when fields are added to [`Timing`]. Unset fields are
+    /// zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let timing = Timing::builder().busy(Duration::from_millis(2)).build();
+    ///
+    /// assert_eq!(timing.busy, Duration::from_millis(2));
+    /// assert!(timing.idle.is_zero());
+    /// ```
+    pub fn builder() -> TimingBuilder {
+        TimingBuilder::default()
+    }
+
+    /// Return `true` if the busy time is greater than `duration`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let timing = Timing::builder().busy(Duration::from_millis(2)).build();
+    ///
+    /// assert!(timing.exceeds_busy(Duration::from_millis(1)));
+    /// assert!(!timing.exceeds_busy(Duration::from_millis(2)));
+    /// ```
+    #[must_use]
+    pub fn exceeds_busy(&self, duration: Duration) -> bool {
+        self.busy > duration
+    }
+
+    /// Return `true` if the sum of idle and busy time is greater than `duration`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let timing = Timing::builder()
+    ///     .idle(Duration::from_millis(2))
+    ///     .busy(Duration::from_millis(2))
+    ///     .build();
+    ///
+    /// assert!(timing.exceeds_total(Duration::from_millis(3)));
+    /// assert!(!timing.exceeds_busy(Duration::from_millis(3)));
+    /// ```
+    #[must_use]
+    pub fn exceeds_total(&self, duration: Duration) -> bool {
+        self.idle.saturating_add(self.busy) > duration
+    }
+
+    /// Busy time in seconds as `f64`.
+    ///
+    /// This is the unit custom [criterion] measurements and most plotting tools expect. To
+    /// benchmark only the CPU portion of a future, excluding awaited I/O, sum up the busy time of
+    /// each iteration in `iter_custom` and return it as the measured duration:
+    ///
+    /// ```ignore
+    /// group.bench_function("parse", |b| {
+    ///     b.to_async(&runtime).iter_custom(|iters| async move {
+    ///         let mut busy = Duration::ZERO;
+    ///         for _ in 0..iters {
+    ///             parse_request().timed(|timing| busy += timing.busy).await;
+    ///         }
+    ///         busy
+    ///     })
+    /// });
+    /// ```
+    ///
+    /// A custom `criterion::measurement::Measurement` can report this value from its `to_f64`.
+    ///
+    /// [criterion]: https://docs.rs/criterion/latest/criterion/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let timing = Timing::builder().busy(Duration::from_millis(1500)).build();
+    ///
+    /// assert_eq!(timing.busy_secs_f64(), 1.5);
+    /// ```
+    #[must_use]
+    pub fn busy_secs_f64(&self) -> f64 {
+        self.busy.as_secs_f64()
+    }
+
+    /// Render the share of busy and idle time of the total time, e.g. `busy 73% / idle 27%`.
+    ///
+    /// Percentages are rounded to whole numbers and always add up to 100%. If the total time is
+    /// zero, `n/a` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let timing = Timing::builder()
+    ///     .idle(Duration::from_millis(27))
+    ///     .busy(Duration::from_millis(73))
+    ///     .build();
+    ///
+    /// assert_eq!(timing.breakdown(), "busy 73% / idle 27%");
+    /// assert_eq!(Timing::ZERO.breakdown(), "n/a");
+    /// ```
+    #[must_use]
+    pub fn breakdown(&self) -> String {
+        let busy = self.busy.as_nanos();
+        let total = busy + self.idle.as_nanos();
+
+        if total == 0 {
+            return "n/a".to_string();
+        }
+
+        let busy = (busy * 100 + total / 2) / total;
+        format!("busy {busy}% / idle {}%", 100 - busy)
+    }
+
+    /// Add two timings, returning `None` if either the idle or the busy time overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let max = Timing::builder().busy(Duration::MAX).build();
+    /// let one = Timing::builder().busy(Duration::from_nanos(1)).build();
+    ///
+    /// assert_eq!(one.checked_add(one).unwrap().busy, Duration::from_nanos(2));
+    /// assert!(max.checked_add(one).is_none());
+    /// ```
+    #[must_use]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            idle: self.idle.checked_add(rhs.idle)?,
+            busy: self.busy.checked_add(rhs.busy)?,
+        })
+    }
+
+    /// Element-wise maximum of the idle and busy times of two timings.
+    ///
+    /// The result does not necessarily correspond to a single measurement, as the largest idle
+    /// and the largest busy time may come from different ones. Fold an iterator of timings with
+    /// [`Iterator::reduce`] to get the worst case of a batch in one pass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let timings = [
+    ///     Timing::builder().idle(ms(5)).busy(ms(1)).build(),
+    ///     Timing::builder().idle(ms(2)).busy(ms(3)).build(),
+    /// ];
+    ///
+    /// let worst = timings.into_iter().reduce(Timing::max).unwrap();
+    ///
+    /// assert_eq!(worst, Timing::builder().idle(ms(5)).busy(ms(3)).build());
+    /// ```
+    #[must_use]
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            idle: self.idle.max(other.idle),
+            busy: self.busy.max(other.busy),
+        }
+    }
+
+    /// Element-wise minimum of the idle and busy times of two timings.
+    ///
+    /// Like [`max`](Self::max), the result does not necessarily correspond to a single
+    /// measurement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let a = Timing::builder().idle(ms(5)).busy(ms(1)).build();
+    /// let b = Timing::builder().idle(ms(2)).busy(ms(3)).build();
+    ///
+    /// assert_eq!(a.min(b), Timing::builder().idle(ms(2)).busy(ms(1)).build());
+    /// ```
+    #[must_use]
+    pub fn min(self, other: Self) -> Self {
+        Self {
+            idle: self.idle.min(other.idle),
+            busy: self.busy.min(other.busy),
+        }
+    }
+
+    /// Divide the idle and busy times by `count`, e.g. to get the timing per item of a future
+    /// processing a batch of `count` items.
+    ///
+    /// Returns zero timing if `count` is zero, since there is no item to attribute time to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let batch = Timing::builder().idle(ms(8)).busy(ms(40)).build();
+    ///
+    /// assert_eq!(batch.per(4), Timing::builder().idle(ms(2)).busy(ms(10)).build());
+    /// assert_eq!(batch.per(0), Timing::ZERO);
+    /// ```
+    #[must_use]
+    pub fn per(self, count: u32) -> Self {
+        if count == 0 {
+            return Self::ZERO;
+        }
+
+        Self {
+            idle: self.idle / count,
+            busy: self.busy / count,
+        }
+    }
+
+    /// Throughput in items per second of a future that processed `count` items, based on the
+    /// total time, i.e. the sum of idle and busy time.
+    ///
+    /// Returns `0.0` if `count` is zero and [`f64::INFINITY`] if `count` is not zero but the total
+    /// time is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::Timing;
+    /// use std::time::Duration;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let batch = Timing::builder().idle(ms(100)).busy(ms(400)).build();
+    ///
+    /// assert_eq!(batch.rate(1000), 2000.0);
+    /// assert_eq!(batch.rate(0), 0.0);
+    /// ```
+    #[must_use]
+    pub fn rate(self, count: u32) -> f64 {
+        if count == 0 {
+            return 0.0;
+        }
+
+        f64::from(count) / self.idle.saturating_add(self.busy).as_secs_f64()
+    }
+
+    /// Round the idle and busy times to the nearest multiple of `granularity`, with halfway cases
+    /// rounded up.
+    ///
+    /// Clocks of different machines resolve time differently, see [`clock_resolution`]. Rounding
+    /// timings measured on several machines to the coarsest of their resolutions makes them
+    /// comparable, e.g. when comparing benchmark results. A zero `granularity` returns the
+    /// timing unchanged. Results not representable as [`Duration`] saturate at
+    /// [`Duration::MAX`].
+    ///
+    /// [`clock_resolution`]: crate::clock_resolution
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::{clock_resolution, Timing};
+    /// use std::time::Duration;
+    ///
+    /// let us = Duration::from_micros;
+    /// let timing = Timing::builder().idle(us(1499)).busy(us(2500)).build();
+    ///
+    /// assert_eq!(
+    ///     timing.round_to(Duration::from_millis(1)),
+    ///     Timing::builder().idle(us(1000)).busy(us(3000)).build(),
+    /// );
+    ///
+    /// // Rounding to the local clock resolution changes a timing by at most half a tick
+    /// let local = timing.round_to(clock_resolution());
+    /// assert!(local.busy <= timing.busy + clock_resolution() / 2);
+    /// ```
+    #[must_use]
+    pub fn round_to(self, granularity: Duration) -> Self {
+        Self {
+            idle: round_duration(self.idle, granularity),
+            busy: round_duration(self.busy, granularity),
+        }
+    }
+}
+
+/// Round `duration` to the nearest multiple of `granularity`, saturating at [`Duration::MAX`].
+fn round_duration(duration: Duration, granularity: Duration) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+    let granularity = granularity.as_nanos();
+
+    if granularity == 0 {
+        return duration;
+    }
+
+    let rounded = (duration.as_nanos() + granularity / 2) / granularity * granularity;
+
+    u64::try_from(rounded / NANOS_PER_SEC).map_or(Duration::MAX, |secs| {
+        // The remainder is below one billion and thus always fits
+        #[allow(clippy::cast_possible_truncation)]
+        Duration::new(secs, (rounded % NANOS_PER_SEC) as u32)
+    })
+}
+
+/// Add the idle and busy times of two timings.
+///
+/// Unlike [`Duration`], the addition saturates at [`Duration::MAX`] instead of panicking, so that
+/// aggregating huge numbers of measurements is always safe. Use [`Timing::checked_add`] to detect
+/// overflows.
+impl Add for Timing {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            idle: self.idle.saturating_add(rhs.idle),
+            busy: self.busy.saturating_add(rhs.busy),
+        }
+    }
+}
+
+impl AddAssign for Timing {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Sum up the idle and busy times of all timings, saturating like [`Add`].
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::Timing;
+/// use std::time::Duration;
+///
+/// let ms = Duration::from_millis;
+/// let timings = [
+///     Timing::builder().idle(ms(1)).busy(ms(2)).build(),
+///     Timing::builder().idle(ms(3)).busy(ms(4)).build(),
+/// ];
+///
+/// let total: Timing = timings.iter().sum();
+///
+/// assert_eq!(total, Timing::builder().idle(ms(4)).busy(ms(6)).build());
+/// assert_eq!(timings.into_iter().sum::<Timing>(), total);
+/// ```
+impl Sum for Timing {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Timing> for Timing {
+    fn sum<I: Iterator<Item = &'a Timing>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Builder for [`Timing`] created with [`Timing::builder`].
+#[derive(Clone, Copy, Debug, Default)]
+#[must_use]
+pub struct TimingBuilder {
+    idle: Duration,
+    busy: Duration,
+}
+
+impl TimingBuilder {
+    /// Set the idle time.
+    pub fn idle(mut self, idle: Duration) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Set the busy time.
+    pub fn busy(mut self, busy: Duration) -> Self {
+        self.busy = busy;
+        self
+    }
+
+    /// Create the [`Timing`].
+    #[must_use]
+    pub fn build(self) -> Timing {
+        Timing {
+            idle: self.idle,
+            busy: self.busy,
+        }
+    }
+}
+
+/// Convert the timing into the total time, i.e. the sum of idle and busy time.
+///
+/// Note that the breakdown into busy and idle time is lost. The sum saturates at
+/// [`Duration::MAX`] instead of overflowing.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::Timing;
+/// use std::time::Duration;
+///
+/// let timing = Timing {
+///     idle: Duration::from_millis(3),
+///     busy: Duration::from_millis(2),
+/// };
+///
+/// let total: Duration = timing.into();
+/// assert_eq!(total, Duration::from_millis(5));
+/// ```
+impl From<Timing> for Duration {
+    fn from(timing: Timing) -> Self {
+        timing.idle.saturating_add(timing.busy)
+    }
+}
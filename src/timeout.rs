@@ -0,0 +1,63 @@
+//! Timed future giving up after a timeout.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+use tokio::time::error::Elapsed;
+use tokio::time::Timeout;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+pin_project! {
+    /// Future for the [`timed_timeout`](super::TimedFutureExt::timed_timeout) method.
+    pub struct TimedTimeout<Fut, F> where Fut: Future, F: FnOnce(Timing) {
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Timeout<Fut>,
+    }
+}
+
+impl<Fut, F> TimedTimeout<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, limit: Duration, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner: tokio::time::timeout(limit, inner),
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedTimeout<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing),
+{
+    type Output = Result<Fut::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(result) => {
+                if let Some(op) = this.op.take() {
+                    op(this.recorder.timing);
+                }
+                Poll::Ready(result)
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Timed future calling a closure with a backtrace if polling exceeds a given threshold.
+
+use std::backtrace::Backtrace;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+use crate::Threshold;
+
+/// Instrument a future to call a closure with a backtrace if a certain threshold is exceeded. The
+/// closure is called for _each_ poll that exceeds the threshold.
+///
+/// The backtrace is captured right after the slow poll returned, regardless of the
+/// `RUST_BACKTRACE` environment variable, and thus shows the code path that polled the
+/// instrumented future. Capturing a backtrace is expensive, so it only happens if the threshold
+/// was exceeded. In general, it is more straightforward to use the [`super::TimedFutureExt`]
+/// extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::warn_if_trace;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let blocking = async {
+///     std::thread::sleep(Duration::from_millis(10));
+/// };
+///
+/// warn_if_trace(blocking, Duration::from_millis(5), |duration, backtrace| {
+///     eprintln!("poll took {duration:?} at\n{backtrace}");
+/// })
+/// .await;
+/// # }
+pub fn warn_if_trace<Fut, F>(
+    fut: Fut,
+    threshold: impl Into<Threshold>,
+    op: F,
+) -> WarnIfTrace<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration, Backtrace),
+{
+    WarnIfTrace::new(fut, threshold.into().as_duration(), op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_trace`] function and
+    /// [`warn_if_trace`](super::TimedFutureExt::warn_if_trace) method.
+    pub struct WarnIfTrace<Fut, F> where Fut: Future, F: Fn(Duration, Backtrace) {
+        threshold: Duration,
+        op: F,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> WarnIfTrace<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration, Backtrace),
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, op: F) -> Self {
+        Self {
+            threshold,
+            op,
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for WarnIfTrace<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration, Backtrace),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let start = clock::now();
+        let mut this = self.project();
+        let result = this.inner.as_mut().poll(cx);
+        let end = clock::now();
+
+        let busy = end - start;
+
+        if busy >= *this.threshold {
+            (*this.op)(busy, Backtrace::force_capture());
+        }
+
+        result
+    }
+}
@@ -0,0 +1,216 @@
+//! Hierarchical timing of labeled sub-futures.
+//!
+//! A [`timed_tree`](super::TimedFutureExt::timed_tree) future collects the timing of all
+//! [`timed_node`](super::TimedFutureExt::timed_node) futures that complete while it is being
+//! polled. Nodes themselves collect the nodes completing while they are polled, so that the
+//! resulting [`TimingNode`] tree mirrors the nesting of the `.await`s. Nodes that are not polled
+//! from within a tree, for example because they were spawned onto another task, are discarded.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+thread_local! {
+    static STACK: RefCell<Vec<Vec<TimingNode>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Labeled timing of a future and the labeled sub-futures it awaited.
+///
+/// The [`Display`](fmt::Display) implementation renders the tree like the call tree of a
+/// profiler, one node per line indented by its depth, with its busy time and the share of its
+/// parent's busy time:
+///
+/// ```text
+/// request 3.1ms (100.0%)
+///   header 1.0ms (32.3%)
+///     parse 0.8ms (80.0%)
+///   body 2.0ms (64.5%)
+/// ```
+///
+/// The shares of the children do not add up to 100% if the parent was busy outside of labeled
+/// sub-futures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimingNode {
+    /// Label of the future.
+    pub label: &'static str,
+    /// Timing of the future including the timing of its children.
+    pub timing: Timing,
+    /// Nodes of the sub-futures in order of completion.
+    pub children: Vec<TimingNode>,
+}
+
+impl TimingNode {
+    /// Write this node and its children indented by `depth` levels.
+    fn fmt_indented(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        depth: usize,
+        parent_busy: Duration,
+    ) -> fmt::Result {
+        let share = if parent_busy.is_zero() {
+            0.0
+        } else {
+            100.0 * self.timing.busy.as_secs_f64() / parent_busy.as_secs_f64()
+        };
+
+        writeln!(
+            f,
+            "{:indent$}{} {:.1?} ({share:.1}%)",
+            "",
+            self.label,
+            self.timing.busy,
+            indent = 2 * depth
+        )?;
+
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1, self.timing.busy)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for TimingNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The root is always all of its own busy time
+        self.fmt_indented(f, 0, self.timing.busy)
+    }
+}
+
+/// Poll `inner` collecting all nodes completing in the meantime into `children`.
+fn poll_collecting<Fut: Future>(
+    recorder: &mut Recorder,
+    inner: Pin<&mut Fut>,
+    cx: &mut Context<'_>,
+    children: &mut Vec<TimingNode>,
+) -> Poll<Fut::Output> {
+    STACK.with(|stack| stack.borrow_mut().push(Vec::new()));
+    let result = recorder.poll(inner, cx);
+    let completed = STACK.with(|stack| stack.borrow_mut().pop().unwrap_or_default());
+    children.extend(completed);
+    result
+}
+
+pin_project! {
+    /// Future for the [`timed_tree`](super::TimedFutureExt::timed_tree) method.
+    pub struct TimedTree<Fut, F> where Fut: Future, F: FnOnce(TimingNode) {
+        label: &'static str,
+        children: Vec<TimingNode>,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedTree<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(TimingNode),
+{
+    pub(crate) fn new(inner: Fut, label: &'static str, op: F) -> Self {
+        Self {
+            label,
+            children: Vec::new(),
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedTree<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(TimingNode),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match poll_collecting(this.recorder, this.inner, cx, this.children) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    op(TimingNode {
+                        label: this.label,
+                        timing: this.recorder.timing,
+                        children: std::mem::take(this.children),
+                    });
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_node`](super::TimedFutureExt::timed_node) method.
+    #[derive(Debug)]
+    pub struct TimedNode<Fut> where Fut: Future {
+        label: &'static str,
+        children: Vec<TimingNode>,
+        recorder: Recorder,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedNode<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, label: &'static str) -> Self {
+        Self {
+            label,
+            children: Vec::new(),
+            recorder: Recorder::new(),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedNode<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let result = poll_collecting(this.recorder, this.inner, cx, this.children);
+
+        if result.is_ready() {
+            let node = TimingNode {
+                label: this.label,
+                timing: this.recorder.timing,
+                children: std::mem::take(this.children),
+            };
+
+            STACK.with(|stack| {
+                if let Some(parent) = stack.borrow_mut().last_mut() {
+                    parent.push(node);
+                }
+            });
+        }
+
+        result
+    }
+}
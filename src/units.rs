@@ -0,0 +1,160 @@
+//! Strongly-typed units for passing timing around as integers.
+
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::time::Duration;
+
+/// A number of nanoseconds.
+///
+/// Use this instead of a bare `u64` wherever a duration is passed around as an integer, e.g. to
+/// metrics systems that only accept integers. This crate uses it for the `busy_ns` and `idle_ns`
+/// fields of serialized [`TimingBaseline`](crate::TimingBaseline)s and for averaging registry
+/// entries. Arithmetic saturates instead of overflowing.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::Nanos;
+/// use std::time::Duration;
+///
+/// let nanos = Nanos::from(Duration::from_micros(3)) + Nanos(500);
+/// assert_eq!(nanos, Nanos(3_500));
+/// assert_eq!(Duration::from(nanos), Duration::from_nanos(3_500));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Nanos(pub u64);
+
+impl Nanos {
+    /// Zero nanoseconds.
+    pub const ZERO: Self = Self(0);
+}
+
+/// Durations exceeding `u64::MAX` nanoseconds, i.e. roughly 584 years, saturate.
+impl From<Duration> for Nanos {
+    fn from(duration: Duration) -> Self {
+        Self(u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX))
+    }
+}
+
+impl From<Nanos> for Duration {
+    fn from(nanos: Nanos) -> Self {
+        Duration::from_nanos(nanos.0)
+    }
+}
+
+impl Add for Nanos {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Nanos {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Nanos {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Nanos {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// (De)serialize a [`Duration`] field as [`Nanos`], with `#[serde(with = "crate::units::as_nanos")]`.
+#[cfg(feature = "serde")]
+pub(crate) mod as_nanos {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Nanos;
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Nanos::from(*duration).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        Nanos::deserialize(deserializer).map(Duration::from)
+    }
+}
+
+/// Threshold on the duration of polls or futures.
+///
+/// The constructors spell out the unit at the call site, e.g. `Threshold::micros(500)` rather
+/// than `Duration::from_millis(500)` where micros were meant. The `warn_if` family of methods
+/// accepts anything convertible into a threshold, including a plain [`Duration`].
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{Threshold, TimedFutureExt};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// assert_eq!(Threshold::millis(2).as_duration(), Duration::from_micros(2_000));
+///
+/// async { 42 }.warn_if(Threshold::micros(500), |_| {}).await;
+/// async { 42 }.warn_if(Duration::from_micros(500), |_| {}).await;
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Threshold(Duration);
+
+impl Threshold {
+    /// Threshold of `micros` microseconds.
+    #[must_use]
+    pub const fn micros(micros: u64) -> Self {
+        Self(Duration::from_micros(micros))
+    }
+
+    /// Threshold of `millis` milliseconds.
+    #[must_use]
+    pub const fn millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis))
+    }
+
+    /// Threshold of `secs` seconds.
+    #[must_use]
+    pub const fn secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    /// The threshold as a [`Duration`].
+    #[must_use]
+    pub const fn as_duration(self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for Threshold {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<Threshold> for Duration {
+    fn from(threshold: Threshold) -> Self {
+        threshold.0
+    }
+}
@@ -0,0 +1,151 @@
+//! Set of futures aggregating their timing as they complete.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::stream::{FuturesUnordered, Stream, StreamExt};
+
+use crate::timed::TimedCollect;
+use crate::Timing;
+
+/// Aggregated timing of the futures completed by a [`TimedUnordered`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Number of completed futures.
+    pub count: u64,
+    /// Summed busy time of all completed futures.
+    pub busy: Duration,
+    /// Summed idle time of all completed futures.
+    pub idle: Duration,
+    /// Busy time of the busiest completed future.
+    pub max_busy: Duration,
+}
+
+impl BatchSummary {
+    fn record(&mut self, timing: Timing) {
+        self.count += 1;
+        self.busy = self.busy.saturating_add(timing.busy);
+        self.idle = self.idle.saturating_add(timing.idle);
+        self.max_busy = self.max_busy.max(timing.busy);
+    }
+}
+
+/// A [`FuturesUnordered`] instrumenting every future pushed into it.
+///
+/// The timing of each future is added to a [`BatchSummary`] when the future completes, so large
+/// fan-outs get aggregate timing without instrumenting each future at the push site. As a
+/// [`Stream`], it yields the outputs in the order of completion. Requires the `futures` feature.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::TimedUnordered;
+/// use futures::StreamExt;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let mut batch = TimedUnordered::new();
+///
+/// for n in 0..10 {
+///     batch.push(async move { n * 2 });
+/// }
+///
+/// let outputs = batch.by_ref().collect::<Vec<_>>().await;
+///
+/// assert_eq!(outputs.len(), 10);
+/// assert_eq!(batch.summary().count, 10);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TimedUnordered<Fut>
+where
+    Fut: Future,
+{
+    inner: FuturesUnordered<TimedCollect<Fut>>,
+    summary: BatchSummary,
+}
+
+impl<Fut> TimedUnordered<Fut>
+where
+    Fut: Future,
+{
+    /// Create an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            inner: FuturesUnordered::new(),
+            summary: BatchSummary::default(),
+        }
+    }
+
+    /// Instrument `fut` and add it to the set.
+    pub fn push(&mut self, fut: Fut) {
+        self.inner.push(TimedCollect::new(fut));
+    }
+
+    /// Number of futures that have not completed yet.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Return `true` if no futures are left.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Aggregated timing of the futures completed so far.
+    #[must_use]
+    pub fn summary(&self) -> BatchSummary {
+        self.summary
+    }
+}
+
+impl<Fut> Default for TimedUnordered<Fut>
+where
+    Fut: Future,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Fut> FromIterator<Fut> for TimedUnordered<Fut>
+where
+    Fut: Future,
+{
+    fn from_iter<I: IntoIterator<Item = Fut>>(iter: I) -> Self {
+        let mut set = Self::new();
+
+        for fut in iter {
+            set.push(fut);
+        }
+
+        set
+    }
+}
+
+impl<Fut> Stream for TimedUnordered<Fut>
+where
+    Fut: Future,
+{
+    type Item = Fut::Output;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.inner.poll_next_unpin(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Ready(Some((output, timing))) => {
+                self.summary.record(timing);
+                Poll::Ready(Some(output))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,170 @@
+//! Busy utilization of futures over rolling time windows.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+use crate::timed::Recorder;
+use crate::Timing;
+
+#[derive(Debug)]
+struct State {
+    window_start: Instant,
+    busy: Duration,
+    previous: Option<f64>,
+}
+
+/// Tracks the fraction of wall-clock time spent busy across many futures.
+///
+/// Time is divided into consecutive windows of fixed length. The busy time of each completed
+/// future is attributed to the window in which it completed. [`UtilizationTracker::utilization`]
+/// returns the busy time of the last complete window divided by the window length, or the
+/// fraction of the current window if no window has completed yet. When tracking all futures of a
+/// worker thread, this approximates how saturated the worker is. Futures running concurrently on
+/// multiple threads can push the value above 1.0.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{TimedFutureExt, UtilizationTracker};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let tracker = UtilizationTracker::new(Duration::from_secs(1));
+///
+/// async {
+///     std::thread::sleep(Duration::from_millis(10));
+/// }
+/// .timed_utilization(&tracker)
+/// .await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// assert!(tracker.utilization() > 0.0);
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UtilizationTracker {
+    window: Duration,
+    state: Mutex<State>,
+}
+
+impl UtilizationTracker {
+    /// Create a tracker with windows of length `window`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        assert!(!window.is_zero(), "window must not be zero");
+
+        Self {
+            window,
+            state: Mutex::new(State {
+                window_start: clock::now(),
+                busy: Duration::ZERO,
+                previous: None,
+            }),
+        }
+    }
+
+    /// Add the busy time of a completed future to the current window.
+    pub fn record(&self, timing: Timing) {
+        let mut state = self.roll();
+        state.busy += timing.busy;
+    }
+
+    /// Fraction of the last complete window spent busy.
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        let state = self.roll();
+
+        state.previous.unwrap_or_else(|| {
+            let elapsed = clock::now() - state.window_start;
+
+            if elapsed.is_zero() {
+                0.0
+            } else {
+                state.busy.as_secs_f64() / elapsed.as_secs_f64()
+            }
+        })
+    }
+
+    /// Lock the state and move on to the current window.
+    fn roll(&self) -> std::sync::MutexGuard<'_, State> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let elapsed = clock::now() - state.window_start;
+
+        if elapsed >= self.window {
+            // If more than one window passed, the windows in between were idle.
+            state.previous = Some(if elapsed >= self.window * 2 {
+                0.0
+            } else {
+                state.busy.as_secs_f64() / self.window.as_secs_f64()
+            });
+
+            let windows = elapsed.as_nanos() / self.window.as_nanos();
+            let skipped = self.window.as_nanos() * windows;
+            state.window_start += Duration::from_nanos(u64::try_from(skipped).unwrap_or(u64::MAX));
+            state.busy = Duration::ZERO;
+        }
+
+        state
+    }
+}
+
+pin_project! {
+    /// Future for the [`timed_utilization`](super::TimedFutureExt::timed_utilization) method.
+    #[derive(Debug)]
+    pub struct TimedUtilization<'a, Fut> where Fut: Future {
+        recorder: Recorder,
+        tracker: Option<&'a UtilizationTracker>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<'a, Fut> TimedUtilization<'a, Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, tracker: &'a UtilizationTracker) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            tracker: Some(tracker),
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for TimedUtilization<'_, Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(tracker) = this.tracker.take() {
+                    tracker.record(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
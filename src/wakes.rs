@@ -0,0 +1,80 @@
+//! Timed future calling a closure after each poll to trace the poll/wake sequence.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+
+/// A single poll of a future traced with
+/// [`trace_wakes`](super::TimedFutureExt::trace_wakes).
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct WakeEvent {
+    /// Number of this poll, starting at 1.
+    pub poll: u64,
+    /// Time spent in this poll.
+    pub busy: Duration,
+    /// Time between the end of the previous poll and the start of this one, i.e. how long it
+    /// took the future to be woken and polled again. Zero for the first poll.
+    pub idle: Duration,
+    /// Whether the poll returned [`Poll::Pending`].
+    pub pending: bool,
+}
+
+pin_project! {
+    /// Future for the [`trace_wakes`](super::TimedFutureExt::trace_wakes) method.
+    pub struct TraceWakes<Fut, F> where Fut: Future, F: Fn(WakeEvent) {
+        recorder: Recorder,
+        polls: u64,
+        op: F,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TraceWakes<Fut, F>
+where
+    Fut: Future,
+    F: Fn(WakeEvent),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            polls: 0,
+            op,
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TraceWakes<Fut, F>
+where
+    Fut: Future,
+    F: Fn(WakeEvent),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let before = this.recorder.timing;
+        let result = this.recorder.poll(this.inner, cx);
+        let after = this.recorder.timing;
+        *this.polls += 1;
+
+        (this.op)(WakeEvent {
+            poll: *this.polls,
+            busy: after.busy.saturating_sub(before.busy),
+            idle: after.idle.saturating_sub(before.idle),
+            pending: result.is_pending(),
+        });
+
+        result
+    }
+}
@@ -0,0 +1,104 @@
+//! Timed future additionally reporting the wall-clock time from first poll to completion.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Instrument a future to record its timing alongside the wall-clock time from the first poll to
+/// completion.
+///
+/// The busy and idle time are measured per poll and their sum approximates the wall-clock time.
+/// Comparing both values shows how much of the future's lifetime was actually accounted for by
+/// the instrumentation. In general, it is more straightforward to use the
+/// [`super::TimedFutureExt`] extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{timed_vs_wall, Timing};
+/// # use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let output = timed_vs_wall(
+///     async {
+///         tokio::time::sleep(Duration::from_micros(10)).await;
+///         42
+///     },
+///     |Timing { idle, busy }, wall| {
+///         assert!(idle + busy <= wall);
+///     },
+/// )
+/// .await;
+///
+/// assert_eq!(output, 42);
+/// # }
+pub fn timed_vs_wall<Fut, F>(fut: Fut, f: F) -> TimedVsWall<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Duration),
+{
+    TimedVsWall::new(fut, f)
+}
+
+pin_project! {
+    /// Future for the [`timed_vs_wall`] function and
+    /// [`timed_vs_wall`](super::TimedFutureExt::timed_vs_wall) method.
+    pub struct TimedVsWall<Fut, F> where Fut: Future, F: FnOnce(Timing, Duration) {
+        first_poll_start: Option<Instant>,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> TimedVsWall<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Duration),
+{
+    pub(crate) fn new(inner: Fut, op: F) -> Self {
+        Self {
+            first_poll_start: None,
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for TimedVsWall<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Timing, Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+        let first_poll_start = *this.first_poll_start.get_or_insert_with(clock::now);
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some(op) = this.op.take() {
+                    let end = this.recorder.last_poll_end().unwrap_or(first_poll_start);
+                    op(this.recorder.timing, end - first_poll_start);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -3,13 +3,21 @@
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use pin_project_lite::pin_project;
 
+use crate::clock;
+use crate::timed::Recorder;
+use crate::Threshold;
+
 /// Instrument a future call a closure if a certain threshold is exceeded. The closure is called
 /// for _each_ poll that exceeds the threshold.
 ///
+/// A poll is considered to exceed the threshold if its duration is greater than or equal to the
+/// threshold. Passing [`Duration::ZERO`] is therefore a supported way to call the closure on every
+/// poll, including polls that took no measurable time.
+///
 /// In general, it is more straightforward to use the [`super::TimedFutureExt`] extension trait to
 /// instrument a future directly.
 ///
@@ -29,12 +37,12 @@ use pin_project_lite::pin_project;
 /// })
 /// .await;
 /// # }
-pub fn warn_if<Fut, F>(fut: Fut, threshold: Duration, op: F) -> WarnIf<Fut, F>
+pub fn warn_if<Fut, F>(fut: Fut, threshold: impl Into<Threshold>, op: F) -> WarnIf<Fut, F>
 where
     Fut: Future,
     F: Fn(Duration),
 {
-    WarnIf::new(fut, threshold, op)
+    WarnIf::new(fut, threshold.into().as_duration(), op)
 }
 
 pin_project! {
@@ -69,10 +77,14 @@ where
     type Output = Fut::Output;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let start = Instant::now();
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let start = clock::now();
         let mut this = self.project();
         let result = this.inner.as_mut().poll(cx);
-        let end = Instant::now();
+        let end = clock::now();
 
         let busy = end - start;
 
@@ -83,3 +95,579 @@ where
         result
     }
 }
+
+/// Instrument a future to run an asynchronous warning if a certain threshold is exceeded. The
+/// closure is called for _each_ poll that exceeds the threshold and returns a future which is
+/// driven to completion before the instrumented future is polled again.
+///
+/// This allows reporting slow polls through asynchronous, backpressured sinks like bounded
+/// channels. The warning future is polled right after the slow poll as part of the same call to
+/// [`Future::poll`], so its own busy time adds to the poll as seen by the executor but not to the
+/// measured duration. While it is pending, the instrumented future is not polled, and if the
+/// instrumented future completed in the slow poll, its output is held back until the warning
+/// completes. The warning future is boxed, which allocates only when the threshold is exceeded.
+///
+/// In general, it is more straightforward to use the [`super::TimedFutureExt`] extension trait to
+/// instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::warn_if_async;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+///
+/// let blocking = async {
+///     std::thread::sleep(Duration::from_millis(10));
+/// };
+///
+/// warn_if_async(blocking, Duration::from_millis(5), |duration| {
+///     let tx = tx.clone();
+///     async move {
+///         tx.send(duration).await.unwrap();
+///     }
+/// })
+/// .await;
+///
+/// # if !cfg!(feature = "disabled") {
+/// drop(tx);
+/// assert!(rx.recv().await.unwrap() >= Duration::from_millis(5));
+/// # }
+/// # }
+pub fn warn_if_async<Fut, F, W>(
+    fut: Fut,
+    threshold: impl Into<Threshold>,
+    op: F,
+) -> WarnIfAsync<Fut, F, W>
+where
+    Fut: Future,
+    F: Fn(Duration) -> W,
+    W: Future<Output = ()>,
+{
+    WarnIfAsync::new(fut, threshold.into().as_duration(), op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_async`] function and
+    /// [`warn_if_async`](super::TimedFutureExt::warn_if_async) method.
+    pub struct WarnIfAsync<Fut, F, W> where Fut: Future, F: Fn(Duration) -> W, W: Future<Output = ()> {
+        threshold: Duration,
+        op: F,
+        // Pending warning, driven to completion before anything else.
+        warning: Option<Pin<Box<W>>>,
+        // Output of the inner future held back until the warning completed.
+        output: Option<Fut::Output>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F, W> WarnIfAsync<Fut, F, W>
+where
+    Fut: Future,
+    F: Fn(Duration) -> W,
+    W: Future<Output = ()>,
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, op: F) -> Self {
+        Self {
+            threshold,
+            op,
+            warning: None,
+            output: None,
+            inner,
+        }
+    }
+}
+
+impl<Fut, F, W> Future for WarnIfAsync<Fut, F, W>
+where
+    Fut: Future,
+    F: Fn(Duration) -> W,
+    W: Future<Output = ()>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let mut this = self.project();
+
+        if let Some(warning) = this.warning.as_mut() {
+            if warning.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            *this.warning = None;
+        }
+
+        if let Some(output) = this.output.take() {
+            return Poll::Ready(output);
+        }
+
+        let start = clock::now();
+        let result = this.inner.as_mut().poll(cx);
+        let busy = clock::now() - start;
+
+        if busy >= *this.threshold {
+            let mut warning = Box::pin((this.op)(busy));
+
+            if warning.as_mut().poll(cx).is_pending() {
+                *this.warning = Some(warning);
+                if let Poll::Ready(output) = result {
+                    *this.output = Some(output);
+                }
+                return Poll::Pending;
+            }
+        }
+
+        result
+    }
+}
+
+/// Instrument a future to call a closure with a severity level if polling exceeds one of several
+/// thresholds. The closure is called for _each_ poll that exceeds the lowest threshold.
+///
+/// The `thresholds` must be sorted in ascending order and may be anything convertible into a
+/// [`Threshold`], including plain [`Duration`]s. The closure receives the index of the highest
+/// threshold that was exceeded, i.e. 0 for the lowest, and the duration of the poll.
+/// This allows mapping threshold bands to severities like warning, error or critical in a single
+/// combinator instead of chaining multiple [`warn_if`] calls. In general, it is more
+/// straightforward to use the [`super::TimedFutureExt`] extension trait to instrument a future
+/// directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::{warn_if_graded, Threshold};
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let blocking = async {
+///     std::thread::sleep(Duration::from_millis(10));
+/// };
+///
+/// let thresholds = [Threshold::millis(1), Threshold::millis(5), Threshold::secs(1)];
+///
+/// warn_if_graded(blocking, thresholds, |level, duration| {
+///     assert_eq!(level, 1);
+///     assert!(duration >= Duration::from_millis(5));
+/// })
+/// .await;
+/// # }
+pub fn warn_if_graded<Fut, F>(
+    fut: Fut,
+    thresholds: impl IntoIterator<Item = impl Into<Threshold>>,
+    op: F,
+) -> WarnIfGraded<Fut, F>
+where
+    Fut: Future,
+    F: Fn(usize, Duration),
+{
+    WarnIfGraded::new(fut, thresholds, op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_graded`] function and
+    /// [`warn_if_graded`](super::TimedFutureExt::warn_if_graded) method.
+    pub struct WarnIfGraded<Fut, F> where Fut: Future, F: Fn(usize, Duration) {
+        thresholds: Vec<Duration>,
+        op: F,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> WarnIfGraded<Fut, F>
+where
+    Fut: Future,
+    F: Fn(usize, Duration),
+{
+    pub(crate) fn new(
+        inner: Fut,
+        thresholds: impl IntoIterator<Item = impl Into<Threshold>>,
+        op: F,
+    ) -> Self {
+        let thresholds: Vec<Duration> = thresholds
+            .into_iter()
+            .map(|threshold| threshold.into().as_duration())
+            .collect();
+
+        debug_assert!(
+            thresholds.windows(2).all(|w| w[0] <= w[1]),
+            "thresholds must be sorted in ascending order"
+        );
+
+        Self {
+            thresholds,
+            op,
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for WarnIfGraded<Fut, F>
+where
+    Fut: Future,
+    F: Fn(usize, Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let start = clock::now();
+        let mut this = self.project();
+        let result = this.inner.as_mut().poll(cx);
+        let end = clock::now();
+
+        let busy = end - start;
+        let exceeded = this
+            .thresholds
+            .partition_point(|threshold| busy >= *threshold);
+
+        if exceeded > 0 {
+            (*this.op)(exceeded - 1, busy);
+        }
+
+        result
+    }
+}
+
+/// Instrument a future to call a closure if its first poll exceeds a certain threshold.
+///
+/// Only the first poll is measured, subsequent polls are passed through without reading the
+/// clock. The first poll often does expensive setup such as lazy initialization, which gets lost
+/// in the aggregated busy time. In general, it is more straightforward to use the
+/// [`super::TimedFutureExt`] extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::warn_if_first_poll;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let cold_start = async {
+///     std::thread::sleep(Duration::from_millis(10));
+///     tokio::task::yield_now().await;
+/// };
+///
+/// warn_if_first_poll(cold_start, Duration::from_millis(5), |duration| {
+///     assert!(duration >= Duration::from_millis(5))
+/// })
+/// .await;
+/// # }
+pub fn warn_if_first_poll<Fut, F>(
+    fut: Fut,
+    threshold: impl Into<Threshold>,
+    op: F,
+) -> WarnIfFirstPoll<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    WarnIfFirstPoll::new(fut, threshold.into().as_duration(), op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_first_poll`] function and
+    /// [`warn_if_first_poll`](super::TimedFutureExt::warn_if_first_poll) method.
+    pub struct WarnIfFirstPoll<Fut, F> where Fut: Future, F: FnOnce(Duration) {
+        threshold: Duration,
+        first_poll_done: bool,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> WarnIfFirstPoll<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, op: F) -> Self {
+        Self {
+            threshold,
+            first_poll_done: false,
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for WarnIfFirstPoll<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") || self.first_poll_done {
+            return self.project().inner.poll(cx);
+        }
+
+        let start = clock::now();
+        let mut this = self.project();
+        let result = this.inner.as_mut().poll(cx);
+        let end = clock::now();
+
+        let busy = end - start;
+        *this.first_poll_done = true;
+
+        if busy >= *this.threshold {
+            if let Some(op) = this.op.take() {
+                op(busy);
+            }
+        }
+
+        result
+    }
+}
+
+/// Instrument a future to call a closure for _each_ poll exceeding `threshold`, ignoring the first
+/// `warmup_polls` polls.
+///
+/// Slow polls while a long-lived future initializes, e.g. a connection performing its handshake,
+/// are often expected. Skipping them avoids false alarms while still catching slow polls in the
+/// steady state. Like [`warn_if`], a poll exceeds the threshold if its duration is greater than or
+/// equal to it. In general, it is more straightforward to use the [`super::TimedFutureExt`]
+/// extension trait to instrument a future directly.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::warn_if_after_warmup;
+/// use std::time::Duration;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let connection = async {
+///     // Slow handshake in the first poll
+///     std::thread::sleep(Duration::from_millis(10));
+///     tokio::task::yield_now().await;
+/// };
+///
+/// warn_if_after_warmup(connection, Duration::from_millis(5), 1, |_| {
+///     unreachable!("only the warmup poll was slow")
+/// })
+/// .await;
+/// # }
+pub fn warn_if_after_warmup<Fut, F>(
+    fut: Fut,
+    threshold: impl Into<Threshold>,
+    warmup_polls: u32,
+    op: F,
+) -> WarnIfAfterWarmup<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration),
+{
+    WarnIfAfterWarmup::new(fut, threshold.into().as_duration(), warmup_polls, op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_after_warmup`] function and
+    /// [`warn_if_after_warmup`](super::TimedFutureExt::warn_if_after_warmup) method.
+    pub struct WarnIfAfterWarmup<Fut, F> where Fut: Future, F: Fn(Duration) {
+        threshold: Duration,
+        warmup_polls: u32,
+        warmup_polls_left: u32,
+        op: F,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, F> WarnIfAfterWarmup<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration),
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, warmup_polls: u32, op: F) -> Self {
+        Self {
+            threshold,
+            warmup_polls,
+            warmup_polls_left: warmup_polls,
+            op,
+            inner,
+        }
+    }
+
+    /// Restart the warmup, ignoring the next `warmup_polls` polls again.
+    ///
+    /// This is meaningful for long-lived futures that go through initialization repeatedly, e.g.
+    /// a connection driver re-establishing a dropped connection, whose next polls are expected to
+    /// be slow again. The stateless [`WarnIf`] and [`WarnIfGraded`] need no reset. In contrast,
+    /// [`WarnIfFirstPoll`] and [`WarnIfCancelled`] are one-shot by design and cannot be reset:
+    /// their closures are `FnOnce` and consumed when called, by the first poll and on
+    /// cancellation respectively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use future_timed::TimedFutureExt;
+    /// use std::pin::pin;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut connection = pin!(std::future::pending::<()>()
+    ///     .warn_if_after_warmup(Duration::from_millis(5), 1, |_| {}));
+    ///
+    /// assert!(futures::poll!(connection.as_mut()).is_pending());
+    ///
+    /// // Reconnected, the next poll is part of the warmup again
+    /// connection.as_mut().reset();
+    /// # }
+    /// ```
+    pub fn reset(self: Pin<&mut Self>) {
+        let this = self.project();
+        *this.warmup_polls_left = *this.warmup_polls;
+    }
+}
+
+impl<Fut, F> Future for WarnIfAfterWarmup<Fut, F>
+where
+    Fut: Future,
+    F: Fn(Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        if *this.warmup_polls_left > 0 {
+            *this.warmup_polls_left -= 1;
+            return this.inner.poll(cx);
+        }
+
+        let start = clock::now();
+        let result = this.inner.poll(cx);
+        let end = clock::now();
+
+        let busy = end - start;
+
+        if busy >= *this.threshold {
+            (*this.op)(busy);
+        }
+
+        result
+    }
+}
+
+/// Instrument a future to call a closure if it is dropped before completion after having been
+/// busy for at least `threshold`.
+///
+/// The closure receives the busy time accumulated until the future was dropped, i.e. work that
+/// was wasted because the future got cancelled, for example by losing a `select!`. It is never
+/// called if the future completes or if it is dropped without any measured busy time, e.g.
+/// before its first poll.
+///
+/// # Examples
+///
+/// ```
+/// use future_timed::warn_if_cancelled;
+/// use std::time::{Duration, Instant};
+/// # #[tokio::main]
+/// # async fn main() {
+/// let expensive = async {
+///     let start = Instant::now();
+///     while start.elapsed() < Duration::from_millis(10) {}
+///     std::future::pending::<()>().await;
+/// };
+///
+/// let expensive = warn_if_cancelled(expensive, Duration::from_millis(5), |wasted| {
+///     assert!(wasted >= Duration::from_millis(5))
+/// });
+///
+/// tokio::select! {
+///     () = expensive => unreachable!(),
+///     () = tokio::task::yield_now() => {},
+/// }
+/// # }
+pub fn warn_if_cancelled<Fut, F>(
+    fut: Fut,
+    threshold: impl Into<Threshold>,
+    op: F,
+) -> WarnIfCancelled<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    WarnIfCancelled::new(fut, threshold.into().as_duration(), op)
+}
+
+pin_project! {
+    /// Future for the [`warn_if_cancelled`] function and
+    /// [`warn_if_cancelled`](super::TimedFutureExt::warn_if_cancelled) method.
+    pub struct WarnIfCancelled<Fut, F> where Fut: Future, F: FnOnce(Duration) {
+        threshold: Duration,
+        recorder: Recorder,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+
+    impl<Fut, F> PinnedDrop for WarnIfCancelled<Fut, F> where Fut: Future, F: FnOnce(Duration) {
+        fn drop(this: Pin<&mut Self>) {
+            let this = this.project();
+            let busy = this.recorder.timing.busy;
+
+            // The closure is taken on completion, so it is only left if the future was cancelled.
+            if let Some(op) = this.op.take() {
+                if busy >= *this.threshold && !busy.is_zero() {
+                    op(busy);
+                }
+            }
+        }
+    }
+}
+
+impl<Fut, F> WarnIfCancelled<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    pub(crate) fn new(inner: Fut, threshold: Duration, op: F) -> Self {
+        Self {
+            threshold,
+            recorder: Recorder::new(),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, F> Future for WarnIfCancelled<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Duration),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                this.op.take();
+                Poll::Ready(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Timed future publishing snapshots of its timing while it runs.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use tokio::sync::mpsc;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::clock;
+use crate::timed::Recorder;
+use crate::Timing;
+
+/// Waker handed to the inner future, recording whether it was woken before waking the task.
+#[derive(Debug)]
+struct WakeFlag {
+    woken: AtomicBool,
+    task: Mutex<Waker>,
+}
+
+impl WakeFlag {
+    /// Wake `task` on future wakes and return whether the inner future was woken since the last
+    /// call.
+    fn take(&self, task: &Waker) -> bool {
+        let mut waker = self.task.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if !waker.will_wake(task) {
+            waker.clone_from(task);
+        }
+
+        drop(waker);
+        self.woken.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl Wake for WakeFlag {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.task
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .wake_by_ref();
+    }
+}
+
+/// Placeholder for the task waker before the first poll.
+struct Noop;
+
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+pin_project! {
+    /// Future for the [`timed_watch`](super::TimedFutureExt::timed_watch) method.
+    #[derive(Debug)]
+    pub struct TimedWatch<Fut> where Fut: Future {
+        recorder: Recorder,
+        interval: Interval,
+        flag: Arc<WakeFlag>,
+        waker: Waker,
+        tx: Option<mpsc::UnboundedSender<Timing>>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> TimedWatch<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, period: Duration) -> (Self, TimingSnapshots) {
+        assert!(!period.is_zero(), "period must not be zero");
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let start = tokio::time::Instant::now() + period;
+        let mut interval = tokio::time::interval_at(start, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // Initially woken so that the first poll polls the inner future
+        let flag = Arc::new(WakeFlag {
+            woken: AtomicBool::new(true),
+            task: Mutex::new(Waker::from(Arc::new(Noop))),
+        });
+
+        let fut = Self {
+            recorder: Recorder::new(),
+            interval,
+            waker: Waker::from(flag.clone()),
+            flag,
+            tx: Some(tx),
+            inner,
+        };
+
+        (fut, TimingSnapshots { rx })
+    }
+}
+
+impl<Fut> Future for TimedWatch<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        // A tick alone must not poll the inner future
+        let result = if this.flag.take(cx.waker()) {
+            let mut inner_cx = Context::from_waker(this.waker);
+            this.recorder.poll(this.inner, &mut inner_cx)
+        } else {
+            Poll::Pending
+        };
+
+        match result {
+            Poll::Pending => {
+                let mut elapsed = false;
+
+                // Polling until pending registers the waker for the next tick.
+                while this.interval.poll_tick(cx).is_ready() {
+                    elapsed = true;
+                }
+
+                if let Some(tx) = this.tx.as_ref().filter(|_| elapsed) {
+                    let mut timing = this.recorder.timing;
+
+                    if let Some(end) = this.recorder.last_poll_end() {
+                        timing.idle += clock::now().saturating_duration_since(end);
+                    }
+
+                    let _ = tx.send(timing);
+                }
+
+                Poll::Pending
+            }
+            Poll::Ready(output) => {
+                if let Some(tx) = this.tx.take() {
+                    let _ = tx.send(this.recorder.timing);
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
+
+/// Stream of timing snapshots returned by the
+/// [`timed_watch`](super::TimedFutureExt::timed_watch) method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct TimingSnapshots {
+    rx: mpsc::UnboundedReceiver<Timing>,
+}
+
+impl Stream for TimingSnapshots {
+    type Item = Timing;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Timing>> {
+        self.rx.poll_recv(cx)
+    }
+}
@@ -0,0 +1,66 @@
+//! Timed future calling a closure on completion only if a predicate on its output holds.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+
+use crate::timed::Recorder;
+use crate::Timing;
+
+pin_project! {
+    /// Future for the [`timed_when`](super::TimedFutureExt::timed_when) method.
+    pub struct TimedWhen<Fut, P, F> where Fut: Future, P: FnOnce(&Fut::Output) -> bool, F: FnOnce(Timing) {
+        recorder: Recorder,
+        pred: Option<P>,
+        op: Option<F>,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut, P, F> TimedWhen<Fut, P, F>
+where
+    Fut: Future,
+    P: FnOnce(&Fut::Output) -> bool,
+    F: FnOnce(Timing),
+{
+    pub(crate) fn new(inner: Fut, pred: P, op: F) -> Self {
+        Self {
+            recorder: Recorder::new(),
+            pred: Some(pred),
+            op: Some(op),
+            inner,
+        }
+    }
+}
+
+impl<Fut, P, F> Future for TimedWhen<Fut, P, F>
+where
+    Fut: Future,
+    P: FnOnce(&Fut::Output) -> bool,
+    F: FnOnce(Timing),
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        match this.recorder.poll(this.inner, cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(output) => {
+                if let Some((pred, op)) = this.pred.take().zip(this.op.take()) {
+                    if pred(&output) {
+                        op(this.recorder.timing);
+                    }
+                }
+                Poll::Ready(output)
+            }
+        }
+    }
+}
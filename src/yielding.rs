@@ -0,0 +1,66 @@
+//! Future yielding to the executor after a poll was busy for too long.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project_lite::pin_project;
+
+use crate::clock;
+
+pin_project! {
+    /// Future for the [`yield_after`](super::TimedFutureExt::yield_after) method.
+    #[derive(Debug)]
+    pub struct YieldAfter<Fut> where Fut: Future {
+        max_poll: Duration,
+        yield_next: bool,
+        #[pin]
+        inner: Fut,
+    }
+}
+
+impl<Fut> YieldAfter<Fut>
+where
+    Fut: Future,
+{
+    pub(crate) fn new(inner: Fut, max_poll: Duration) -> Self {
+        Self {
+            max_poll,
+            yield_next: false,
+            inner,
+        }
+    }
+}
+
+impl<Fut> Future for YieldAfter<Fut>
+where
+    Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if cfg!(feature = "disabled") {
+            return self.project().inner.poll(cx);
+        }
+
+        let this = self.project();
+
+        if *this.yield_next {
+            // Reschedule right away, the executor gets a chance to run other tasks first
+            *this.yield_next = false;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        let start = clock::now();
+        let result = this.inner.poll(cx);
+        let busy = clock::now() - start;
+
+        if result.is_pending() && busy > *this.max_poll {
+            *this.yield_next = true;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,265 @@
+//! Integration tests for the types aggregating timing data.
+
+use future_timed::{
+    summarize, BusyInterval, ConcurrentTiming, FixedHistogram, RecentTimings, RetryTiming, Timing,
+    TimingAccumulator, TimingHistogram, TimingNode, TimingRegistry,
+};
+use std::time::{Duration, Instant};
+
+#[test]
+fn histogram_buckets() {
+    let ms = Duration::from_millis;
+    let mut histogram = TimingHistogram::with_buckets(&[ms(10), ms(1), ms(5)]);
+
+    for duration in [ms(0), ms(1), ms(2), ms(5), ms(7), ms(50)] {
+        histogram.record(duration);
+    }
+
+    assert_eq!(
+        histogram.buckets(),
+        &[(ms(1), 2), (ms(5), 2), (ms(10), 1), (Duration::MAX, 1)]
+    );
+    assert_eq!(histogram.count(), 6);
+}
+
+#[test]
+fn fixed_histogram_buckets() {
+    const BOUNDARIES: [Duration; 3] = [
+        Duration::from_millis(1),
+        Duration::from_millis(5),
+        Duration::from_millis(10),
+    ];
+
+    let ms = Duration::from_millis;
+    let mut histogram = FixedHistogram::new(BOUNDARIES);
+
+    for duration in [ms(0), ms(1), ms(2), ms(5), ms(7), ms(50)] {
+        histogram.record(duration);
+    }
+
+    assert_eq!(histogram.counts(), &[2, 2, 1]);
+    assert_eq!(histogram.overflow(), 1);
+    assert_eq!(histogram.count(), 6);
+}
+
+#[test]
+#[should_panic(expected = "strictly ascending")]
+fn fixed_histogram_rejects_unsorted_boundaries() {
+    let _ = FixedHistogram::new([Duration::from_millis(5), Duration::from_millis(1)]);
+}
+
+#[test]
+fn concurrent_timing_merges_overlap() {
+    let base = Instant::now();
+    let at = |ms| base + Duration::from_millis(ms);
+    let interval = |start, end| BusyInterval {
+        start: at(start),
+        end: at(end),
+    };
+
+    let concurrent = ConcurrentTiming::new();
+    concurrent.record(&[interval(0, 10), interval(20, 30)]);
+    concurrent.record(&[interval(5, 15), interval(40, 45)]);
+    concurrent.record(&[interval(25, 26)]);
+
+    assert_eq!(concurrent.busy(), Duration::from_millis(30));
+    assert_eq!(concurrent.summed_busy(), Duration::from_millis(36));
+}
+
+#[test]
+fn timing_addition_saturates() {
+    let max = Timing::builder().idle(Duration::MAX).build();
+    let one = Timing::builder()
+        .idle(Duration::from_secs(1))
+        .busy(Duration::from_secs(1))
+        .build();
+
+    let mut sum = max;
+    sum += one;
+
+    assert_eq!(sum.idle, Duration::MAX);
+    assert_eq!(sum.busy, Duration::from_secs(1));
+    assert_eq!(max + one, sum);
+    assert_eq!(max.checked_add(one), None);
+}
+
+#[test]
+fn accumulators_are_interchangeable() {
+    fn accumulate_twice(accumulator: &mut dyn TimingAccumulator, timing: Timing) {
+        accumulator.accumulate(timing);
+        accumulator.accumulate(timing);
+    }
+
+    let ms = Duration::from_millis;
+    let timing = Timing::builder().idle(ms(1)).busy(ms(2)).build();
+
+    let mut sum = Timing::ZERO;
+    accumulate_twice(&mut sum, timing);
+    assert_eq!(sum, timing + timing);
+
+    let mut histogram = TimingHistogram::with_buckets(&[ms(1)]);
+    accumulate_twice(&mut histogram, timing);
+    assert_eq!(histogram.buckets(), &[(ms(1), 0), (Duration::MAX, 2)]);
+
+    let mut retries = RetryTiming::new();
+    accumulate_twice(&mut retries, timing);
+    assert_eq!(retries.attempts(), 2);
+}
+
+#[test]
+fn recent_timings_overwrite_oldest() {
+    let ms = Duration::from_millis;
+    let recent = RecentTimings::new(3);
+    assert!(recent.stats().is_none());
+
+    for busy in 1..=5 {
+        recent.record(Timing::builder().idle(ms(1)).busy(ms(busy)).build());
+    }
+
+    let busy: Vec<_> = recent.iter().map(|timing| timing.busy).collect();
+    assert_eq!(busy, [ms(3), ms(4), ms(5)]);
+
+    let (mean, max) = recent.stats().unwrap();
+    assert_eq!(mean, Timing::builder().idle(ms(1)).busy(ms(4)).build());
+    assert_eq!(max, Timing::builder().idle(ms(1)).busy(ms(5)).build());
+}
+
+#[test]
+fn registry_compares_variant_means() {
+    let ms = Duration::from_millis;
+    let registry = TimingRegistry::new();
+
+    for busy in [2, 4] {
+        registry.record("v1", Timing::builder().busy(ms(busy)).build());
+    }
+    registry.record("v2", Timing::builder().busy(ms(1)).build());
+
+    assert!(registry.compare("v1", "v3").is_none());
+
+    let comparison = registry.compare("v1", "v2").unwrap();
+    assert_eq!(comparison.baseline.mean().busy, ms(3));
+    assert_eq!(comparison.candidate.mean().busy, ms(1));
+    assert!((comparison.busy_ratio() - 1.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn timing_sums_over_slice() {
+    let ms = Duration::from_millis;
+    let timings: &[Timing] = &[
+        Timing::builder().idle(ms(1)).busy(ms(2)).build(),
+        Timing::builder().idle(ms(3)).busy(ms(4)).build(),
+        Timing::builder().idle(Duration::MAX).build(),
+    ];
+
+    let total: Timing = timings.iter().sum();
+    assert_eq!(total.idle, Duration::MAX);
+    assert_eq!(total.busy, ms(6));
+    assert_eq!(timings.iter().copied().sum::<Timing>(), total);
+    assert_eq!(std::iter::empty::<Timing>().sum::<Timing>(), Timing::ZERO);
+}
+
+#[test]
+fn summarize_known_dataset() {
+    let ms = Duration::from_millis;
+    // Busy times 1..=100ms in reverse order, idle times constant
+    let timings: Vec<Timing> = (1..=100)
+        .rev()
+        .map(|i| Timing::builder().busy(ms(i)).idle(ms(2)).build())
+        .collect();
+
+    let summary = summarize(&timings);
+    assert_eq!(summary.count, 100);
+    assert_eq!(summary.total.busy, ms(5050));
+    assert_eq!(summary.total.idle, ms(200));
+    assert_eq!(summary.mean.busy, Duration::from_micros(50_500));
+    assert_eq!(summary.mean.idle, ms(2));
+    assert_eq!(summary.min.busy, ms(1));
+    assert_eq!(summary.max.busy, ms(100));
+    assert_eq!(summary.p50.busy, ms(50));
+    assert_eq!(summary.p90.busy, ms(90));
+    assert_eq!(summary.p99.busy, ms(99));
+    assert_eq!(summary.p99.idle, ms(2));
+
+    let single = summarize(&timings[..1]);
+    assert_eq!(single.p50, timings[0]);
+    assert_eq!(single.p99, timings[0]);
+
+    let empty = summarize(&[]);
+    assert_eq!(empty.count, 0);
+    assert_eq!(empty.max, Timing::ZERO);
+
+    let table = summary.to_string();
+    assert!(table.starts_with("count: 100\n"));
+    assert_eq!(table.lines().count(), 4);
+    assert!(table.lines().nth(2).unwrap().starts_with("busy"));
+    assert!(table.contains("50.5ms"));
+}
+
+#[test]
+fn timing_tree_renders_indented() {
+    let ms = Duration::from_millis;
+    let node = |label, busy, children| TimingNode {
+        label,
+        timing: Timing::builder().busy(busy).build(),
+        children,
+    };
+
+    let root = node(
+        "request",
+        ms(8),
+        vec![
+            node("header", ms(2), vec![node("parse", ms(1), vec![])]),
+            node("body", ms(4), vec![node("empty", Duration::ZERO, vec![])]),
+        ],
+    );
+
+    assert_eq!(
+        root.to_string(),
+        concat!(
+            "request 8.0ms (100.0%)\n",
+            "  header 2.0ms (25.0%)\n",
+            "    parse 1.0ms (50.0%)\n",
+            "  body 4.0ms (50.0%)\n",
+            "    empty 0.0ns (0.0%)\n",
+        )
+    );
+
+    let idle = node(
+        "idle",
+        Duration::ZERO,
+        vec![node("child", Duration::ZERO, vec![])],
+    );
+    assert_eq!(
+        idle.to_string(),
+        "idle 0.0ns (0.0%)\n  child 0.0ns (0.0%)\n"
+    );
+}
+
+#[test]
+fn timing_rounds_at_boundaries() {
+    let ns = Duration::from_nanos;
+    let round = |nanos, granularity| {
+        Timing::builder()
+            .busy(ns(nanos))
+            .build()
+            .round_to(ns(granularity))
+            .busy
+    };
+
+    assert_eq!(round(0, 10), ns(0));
+    assert_eq!(round(4, 10), ns(0));
+    assert_eq!(round(5, 10), ns(10));
+    assert_eq!(round(14, 10), ns(10));
+    assert_eq!(round(15, 10), ns(20));
+    assert_eq!(round(20, 10), ns(20));
+    assert_eq!(round(7, 0), ns(7));
+    assert_eq!(round(1_999_999_999, 1_000_000_000), Duration::from_secs(2));
+
+    let timing = Timing::builder()
+        .idle(Duration::MAX)
+        .busy(Duration::from_millis(1500))
+        .build()
+        .round_to(Duration::from_secs(1));
+    assert_eq!(timing.idle, Duration::MAX);
+    assert_eq!(timing.busy, Duration::from_secs(2));
+}
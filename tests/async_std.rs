@@ -0,0 +1,88 @@
+//! Integration tests running on the async-std runtime.
+
+// async-std pulls in older versions of some of its dependencies.
+#![allow(clippy::multiple_crate_versions)]
+#![cfg(not(feature = "disabled"))]
+
+use future_timed::{timed, warn_if, TimedFutureExt, Timing};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Block the executor by spinning, which consumes both wall-clock and CPU time.
+fn spin(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+}
+
+#[async_std::test]
+async fn never_yield() {
+    let output = timed(async { 42 }, |Timing { idle, busy }| {
+        assert!(idle.is_zero());
+        assert!(!busy.is_zero());
+    })
+    .await;
+
+    assert_eq!(output, 42);
+}
+
+#[async_std::test]
+async fn short_async_sleep() {
+    // async-std checks the timer deadline when it is first polled and the first poll registering
+    // the timer can take longer than a few microseconds, in which case the sleep completes without
+    // ever being idle. Sleep long enough for the future to reliably yield.
+    let output = async {
+        async_std::task::sleep(Duration::from_millis(1)).await;
+        42
+    }
+    .timed(|Timing { idle, busy }| {
+        assert!(idle > Duration::from_micros(10));
+        assert!(!busy.is_zero());
+    })
+    .await;
+
+    assert_eq!(output, 42);
+}
+
+#[async_std::test]
+async fn more_busy_time() {
+    let output = timed(
+        async {
+            spin(Duration::from_micros(200));
+            async_std::task::sleep(Duration::from_millis(1)).await;
+            42
+        },
+        |Timing { idle, busy }| {
+            assert!(idle > Duration::from_micros(10));
+            assert!(busy > Duration::from_micros(200));
+        },
+    )
+    .await;
+
+    assert_eq!(output, 42);
+}
+
+#[async_std::test]
+async fn warn_if_exceeds_threshold() {
+    let blocking = async {
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    warn_if(blocking, Duration::from_millis(5), |duration| {
+        assert!(duration >= Duration::from_millis(5));
+    })
+    .await;
+}
+
+#[async_std::test]
+async fn warn_if_zero_threshold_fires_on_every_poll() {
+    let polls = AtomicUsize::new(0);
+
+    warn_if(async_std::task::yield_now(), Duration::ZERO, |_| {
+        polls.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert_eq!(polls.load(Ordering::Relaxed), 2);
+}
@@ -0,0 +1,83 @@
+//! Integration tests for the `disabled` feature.
+
+#![cfg(feature = "disabled")]
+
+use future_timed::TimedFutureExt;
+use std::time::Duration;
+
+#[tokio::test]
+async fn closures_are_never_called() {
+    let output = async {
+        tokio::task::yield_now().await;
+        42
+    }
+    .timed(|_| unreachable!())
+    .warn_if(Duration::ZERO, |_| unreachable!())
+    .elapsed(|_| unreachable!())
+    .await;
+
+    assert_eq!(output, 42);
+}
+
+#[tokio::test]
+async fn collected_timing_is_zero() {
+    let (output, timing) = async {
+        std::thread::sleep(Duration::from_millis(1));
+        tokio::task::yield_now().await;
+        42
+    }
+    .timed_collect()
+    .await;
+
+    assert_eq!(output, 42);
+    assert!(timing.busy.is_zero());
+    assert!(timing.idle.is_zero());
+}
+
+#[tokio::test]
+async fn timing_control_is_a_no_op_handle() {
+    let (fut, control) = async {
+        let control = future_timed::TimingControl::current().unwrap();
+        control.pause();
+        assert!(!control.is_paused());
+        42
+    }
+    .timed_controlled(|_| unreachable!());
+
+    assert_eq!(fut.await, 42);
+    assert!(!control.is_paused());
+    assert!(future_timed::TimingControl::current().is_some());
+}
+
+#[tokio::test]
+async fn timed_controllable_still_aborts() {
+    let result = std::future::pending::<()>()
+        .timed_controllable(|_| std::ops::ControlFlow::Break(()))
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn timing_scope_records_nothing() {
+    let registry = future_timed::TimingRegistry::new();
+
+    async {
+        let _guard = future_timed::timing_scope("label", &registry).await_guard();
+        tokio::task::yield_now().await;
+    }
+    .timed(|_| unreachable!())
+    .await;
+
+    assert!(registry.get("label").is_none());
+}
+
+#[tokio::test]
+async fn timed_gated_releases_guard_on_completion() {
+    let semaphore = tokio::sync::Semaphore::new(1);
+    let mut gated = std::pin::pin!(async {}.timed_gated(semaphore.acquire(), |_| unreachable!()));
+
+    // The completed future is still alive but must not hold the permit anymore
+    assert!(futures::poll!(gated.as_mut()).is_ready());
+    assert_eq!(semaphore.available_permits(), 1);
+}
@@ -0,0 +1,40 @@
+//! Integration tests for the global reporter.
+//!
+//! The reporter can only be set once per process, so all tests depending on it live in this
+//! binary and share a single reporter.
+
+#![cfg(not(feature = "disabled"))]
+
+use future_timed::{set_global_reporter, TimedFutureExt, Timing};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+static REPORTED_BUSY_NANOS: AtomicU64 = AtomicU64::new(0);
+
+fn install() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        set_global_reporter(|timing: Timing| {
+            let nanos = u64::try_from(timing.busy.as_nanos()).unwrap_or(u64::MAX);
+            REPORTED_BUSY_NANOS.fetch_add(nanos, Ordering::Relaxed);
+        })
+        .unwrap();
+    });
+}
+
+#[tokio::test]
+async fn timed_default_reports_to_global_reporter() {
+    install();
+
+    async {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_micros(200) {}
+    }
+    .timed_default()
+    .await;
+
+    assert!(REPORTED_BUSY_NANOS.load(Ordering::Relaxed) > 0);
+    assert!(set_global_reporter(|_| {}).is_err());
+}
@@ -1,7 +1,59 @@
 //! Integration tests running on the tokio runtime.
 
-use future_timed::{timed, warn_if, TimedFutureExt, Timing};
-use std::time::Duration;
+#![cfg(not(feature = "disabled"))]
+
+use future_timed::{
+    skip_timing, take_flame_folded, timed, timed_channel, timed_poll_fn, timed_select,
+    timing_mark_end, timing_mark_start, timing_scope, warn_if, IdleKind, RetryTiming, SamplerState,
+    SelectTiming, Selected, Timed, TimedFutureExt, Timing, TimingControl, TimingHistogram,
+    TimingRegistry, UtilizationTracker, WakeEvent,
+};
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// Block the executor by spinning, which consumes both wall-clock and CPU time.
+///
+/// With `cpu-time`, this spins until the thread consumed `duration` of CPU time as well, which
+/// takes longer if the thread is preempted while spinning.
+fn spin(duration: Duration) {
+    let start = Instant::now();
+    let cpu_start = thread_cpu_time();
+
+    while start.elapsed() < duration
+        || thread_cpu_time()
+            .zip(cpu_start)
+            .is_some_and(|(now, cpu_start)| now.saturating_sub(cpu_start) < duration)
+    {
+        std::hint::spin_loop();
+    }
+}
+
+/// CPU time consumed by the calling thread, if busy time is measured as CPU time.
+#[cfg(all(feature = "cpu-time", target_os = "linux"))]
+#[allow(clippy::unnecessary_wraps)]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // SAFETY: `ts` is a valid, writable timespec.
+    let result = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+    assert_eq!(result, 0);
+
+    Some(Duration::new(
+        u64::try_from(ts.tv_sec).unwrap(),
+        u32::try_from(ts.tv_nsec).unwrap(),
+    ))
+}
+
+/// CPU time consumed by the calling thread, if busy time is measured as CPU time.
+#[cfg(not(all(feature = "cpu-time", target_os = "linux")))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
 
 #[tokio::test]
 async fn never_yield() {
@@ -33,7 +85,7 @@ async fn short_async_sleep() {
 async fn more_busy_time() {
     let output = timed(
         async {
-            std::thread::sleep(Duration::from_micros(200));
+            spin(Duration::from_micros(200));
             tokio::time::sleep(Duration::from_micros(10)).await;
             42
         },
@@ -58,3 +110,1514 @@ async fn warn_if_exceeds_threshold() {
     })
     .await;
 }
+
+#[tokio::test]
+async fn wall_clock_covers_busy_and_idle() {
+    let output = async {
+        spin(Duration::from_micros(200));
+        tokio::time::sleep(Duration::from_micros(10)).await;
+        42
+    }
+    .timed_vs_wall(|Timing { idle, busy }, wall| {
+        assert!(busy > Duration::from_micros(200));
+        assert!(idle + busy <= wall);
+    })
+    .await;
+
+    assert_eq!(output, 42);
+}
+
+#[cfg(all(feature = "cpu-time", target_os = "linux"))]
+#[tokio::test]
+async fn cpu_time_excludes_sleeping() {
+    timed(
+        async {
+            std::thread::sleep(Duration::from_millis(10));
+        },
+        |Timing { busy, .. }| {
+            assert!(busy < Duration::from_millis(10));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn warn_if_zero_threshold_fires_on_every_poll() {
+    let polls = AtomicUsize::new(0);
+
+    warn_if(tokio::task::yield_now(), Duration::ZERO, |_| {
+        polls.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert_eq!(polls.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn retry_timing_sums_attempts() {
+    let retries = RetryTiming::new();
+
+    for _ in 0..3 {
+        async {
+            spin(Duration::from_micros(100));
+        }
+        .timed(retries.recorder())
+        .await;
+    }
+
+    assert_eq!(retries.attempts(), 3);
+    assert!(retries.timing().busy > Duration::from_micros(300));
+}
+
+#[tokio::test]
+async fn timed_channel_ignores_dropped_receiver() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    drop(rx);
+
+    let output = timed_channel(async { 42 }, tx).await;
+    assert_eq!(output, 42);
+}
+
+#[tokio::test]
+async fn timing_scope_excludes_suspension() {
+    let registry = TimingRegistry::new();
+
+    async {
+        spin(Duration::from_micros(100));
+        let _guard = timing_scope("section", &registry).await_guard();
+        spin(Duration::from_micros(200));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    .timed(|_| {})
+    .await;
+
+    let section = registry.get("section").unwrap();
+    assert_eq!(section.count, 1);
+    assert!(section.timing.busy >= Duration::from_micros(200));
+    assert!(section.timing.busy < Duration::from_millis(5));
+    assert!(section.timing.idle >= Duration::from_millis(5));
+}
+
+/// Busy for `busy`, then idle for `idle`, then busy for `busy` again.
+async fn busy_idle_busy(busy: Duration, idle: Duration) -> u64 {
+    spin(busy);
+    tokio::time::sleep(idle).await;
+    spin(busy);
+    42
+}
+
+/// Return an `impl Future` without being an `async fn` itself.
+#[allow(clippy::manual_async_fn)]
+fn impl_future(busy: Duration) -> impl std::future::Future<Output = u64> {
+    async move {
+        spin(busy);
+        42
+    }
+}
+
+/// Instrument any future in generic code.
+async fn generic_timed<Fut: std::future::Future>(fut: Fut) -> (Fut::Output, Timing) {
+    let mut timing = None;
+    let output = fut.timed(|t| timing = Some(t)).await;
+    (output, timing.unwrap())
+}
+
+/// Return a slice of `text` borrowed across an await point.
+async fn first_word(text: &str) -> &str {
+    tokio::task::yield_now().await;
+    text.split_whitespace().next().unwrap_or_default()
+}
+
+#[tokio::test]
+async fn async_fn_call_is_instrumented_directly() {
+    let output = busy_idle_busy(Duration::from_millis(1), Duration::from_millis(5))
+        .timed(|Timing { idle, busy }| {
+            assert!(busy >= Duration::from_millis(2));
+            assert!(idle >= Duration::from_millis(5));
+        })
+        .await;
+
+    assert_eq!(output, 42);
+
+    let (output, timing) = impl_future(Duration::from_millis(1)).timed_collect().await;
+    assert_eq!(output, 42);
+    assert!(timing.busy >= Duration::from_millis(1));
+    assert!(timing.idle.is_zero());
+}
+
+#[tokio::test]
+async fn async_fn_call_is_instrumented_in_generic_code() {
+    let (output, timing) = generic_timed(busy_idle_busy(
+        Duration::from_millis(1),
+        Duration::from_millis(5),
+    ))
+    .await;
+
+    assert_eq!(output, 42);
+    assert!(timing.busy >= Duration::from_millis(2));
+    assert!(timing.idle >= Duration::from_millis(5));
+}
+
+#[tokio::test]
+async fn borrowed_outputs_are_passed_through() {
+    // Not `'static`, the outputs below borrow from this local
+    let text = String::from("hello borrowed world");
+
+    let (output, timing) = first_word(&text).timed_collect().await;
+    assert_eq!(output, "hello");
+    assert!(timing.idle > Duration::ZERO);
+
+    let output = first_word(&text).timed(|_| {}).await;
+    assert_eq!(output, "hello");
+
+    let output = first_word(&text)
+        .warn_if(Duration::from_secs(1), |_| panic!("poll took too long"))
+        .await;
+    assert_eq!(output, "hello");
+
+    let output = warn_if(first_word(&text), Duration::from_secs(1), |_| {
+        panic!("poll took too long")
+    })
+    .await;
+    assert_eq!(output, "hello");
+
+    // Closures receiving the output by reference can inspect the borrowed data
+    let mut reported = false;
+    let output = first_word(&text)
+        .timed_when(|word| word.starts_with('h'), |_| reported = true)
+        .await;
+    assert_eq!(output, "hello");
+    assert!(reported);
+
+    // Errors borrowing from the future's data can be mapped with the timing
+    let result = async { text.parse::<u8>().map_err(|_| text.as_str()) }
+        .timed_err_context(|err, timing| format!("{err} after {timing:?}"))
+        .await;
+    assert!(result
+        .unwrap_err()
+        .starts_with("hello borrowed world after"));
+
+    let (output, timing) = generic_timed(first_word(&text)).await;
+    assert_eq!(output, "hello");
+    assert!(timing.idle > Duration::ZERO);
+}
+
+#[tokio::test]
+async fn wrap_pinned_measures_from_wrapping() {
+    let mut fut = Box::pin(busy_idle_busy(
+        Duration::from_millis(5),
+        Duration::from_millis(5),
+    ));
+    assert!(futures::poll!(fut.as_mut()).is_pending());
+
+    let mut timing = None;
+    let output = Timed::wrap_pinned(fut.as_mut(), |t| timing = Some(t)).await;
+    assert_eq!(output, 42);
+
+    // Only the second busy section after the sleep is measured
+    let timing = timing.unwrap();
+    assert!(timing.busy >= Duration::from_millis(5));
+    assert!(timing.busy < Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn boxed_and_pinned_async_fn_calls_are_instrumented() {
+    let boxed: std::pin::Pin<Box<dyn std::future::Future<Output = u64> + Send>> =
+        Box::pin(impl_future(Duration::from_millis(1)));
+
+    let (output, timing) = boxed.timed_collect().await;
+    assert_eq!(output, 42);
+    assert!(timing.busy >= Duration::from_millis(1));
+
+    let mut pinned = pin!(busy_idle_busy(Duration::ZERO, Duration::from_millis(5)));
+    let (output, timing) = pinned.as_mut().timed_collect().await;
+    assert_eq!(output, 42);
+    assert!(timing.idle >= Duration::from_millis(5));
+}
+
+#[tokio::test]
+async fn registry_merges_static_and_owned_labels() {
+    let registry = TimingRegistry::new();
+
+    async {}.timed(registry.recorder("users")).await;
+
+    for name in ["users", "orders"] {
+        async {}
+            .timed_named_owned(name.to_string(), &registry)
+            .await;
+    }
+
+    let snapshot = registry.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[0].0, "orders");
+    assert_eq!(snapshot[0].1.count, 1);
+    assert_eq!(snapshot[1].0, "users");
+    assert_eq!(snapshot[1].1.count, 2);
+}
+
+#[tokio::test]
+async fn registry_percentiles_reset_with_window() {
+    let registry =
+        TimingRegistry::with_histogram_buckets(&[Duration::from_millis(1), Duration::from_secs(1)]);
+
+    for _ in 0..9 {
+        async {}.timed_named_hist("op", &registry).await;
+    }
+    async {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(2) {}
+    }
+    .timed_named_hist("op", &registry)
+    .await;
+
+    assert_eq!(registry.get("op").unwrap().count, 10);
+    assert_eq!(registry.histogram("op").unwrap().count(), 10);
+    assert_eq!(
+        registry.percentile("op", 0.5),
+        Some(Duration::from_millis(1))
+    );
+    assert_eq!(registry.percentile("op", 1.0), Some(Duration::from_secs(1)));
+
+    registry.reset();
+    assert!(registry.get("op").is_none());
+    assert_eq!(registry.percentile("op", 0.5), None);
+}
+
+#[tokio::test]
+async fn warn_if_graded_reports_highest_level() {
+    let levels = std::sync::Mutex::new(Vec::new());
+    let thresholds = [
+        Duration::from_millis(1),
+        Duration::from_millis(5),
+        Duration::from_secs(10),
+    ];
+
+    async {
+        std::thread::sleep(Duration::from_millis(2));
+        tokio::task::yield_now().await;
+        std::thread::sleep(Duration::from_millis(10));
+        tokio::task::yield_now().await;
+    }
+    .warn_if_graded(thresholds, |level, _| {
+        levels.lock().unwrap().push(level);
+    })
+    .await;
+
+    assert_eq!(*levels.lock().unwrap(), [0, 1]);
+}
+
+#[tokio::test]
+async fn utilization_of_completed_window() {
+    let tracker = UtilizationTracker::new(Duration::from_millis(100));
+
+    async {
+        spin(Duration::from_millis(50));
+    }
+    .timed_utilization(&tracker)
+    .await;
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    let utilization = tracker.utilization();
+    assert!(utilization >= 0.5, "{utilization}");
+    assert!(utilization < 1.0, "{utilization}");
+}
+
+#[tokio::test]
+#[should_panic(expected = "exceeds the limit")]
+async fn assert_idle_under_panics() {
+    tokio::time::sleep(Duration::from_millis(5))
+        .assert_idle_under(Duration::from_millis(1))
+        .await;
+}
+
+#[tokio::test]
+async fn timed_flame_records_self_time_per_stack() {
+    async {
+        spin(Duration::from_millis(2));
+
+        async {
+            spin(Duration::from_millis(4));
+            tokio::task::yield_now().await;
+        }
+        .timed_flame("child")
+        .await;
+    }
+    .timed_flame("parent")
+    .await;
+
+    let folded = take_flame_folded();
+    let micros = |stack: &str| {
+        folded
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix(stack)?
+                    .strip_prefix(' ')?
+                    .parse::<u128>()
+                    .ok()
+            })
+            .unwrap()
+    };
+
+    assert!((2000..4000).contains(&micros("parent")), "{folded}");
+    assert!(micros("parent;child") >= 4000, "{folded}");
+}
+
+#[tokio::test]
+async fn paused_busy_time_is_not_counted() {
+    let (fut, control) = async {
+        let control = TimingControl::current().unwrap();
+        spin(Duration::from_millis(2));
+        control.pause();
+        spin(Duration::from_millis(20));
+        tokio::task::yield_now().await;
+        spin(Duration::from_millis(20));
+        control.resume();
+        spin(Duration::from_millis(2));
+    }
+    .timed_controlled(|Timing { busy, .. }| {
+        assert!(busy >= Duration::from_millis(4));
+        assert!(busy < Duration::from_millis(20));
+    });
+
+    fut.await;
+    assert!(!control.is_paused());
+    assert!(TimingControl::current().is_none());
+}
+
+#[tokio::test]
+async fn skipped_scope_is_not_counted() {
+    let (fut, control) = async {
+        spin(Duration::from_millis(2));
+        skip_timing()
+            .await_scope(async {
+                spin(Duration::from_millis(20));
+                tokio::task::yield_now().await;
+                spin(Duration::from_millis(20));
+            })
+            .await;
+        spin(Duration::from_millis(2));
+    }
+    .timed_controlled(|Timing { busy, .. }| {
+        assert!(busy >= Duration::from_millis(4));
+        assert!(busy < Duration::from_millis(20));
+    });
+
+    fut.await;
+    assert!(!control.is_paused());
+}
+
+#[tokio::test]
+async fn incremental_sessions_report_own_timing() {
+    let sessions = std::sync::Mutex::new(Vec::new());
+    let mut pending = true;
+
+    // Pending once per session, then ready
+    let restartable = std::future::poll_fn(|cx| {
+        pending = !pending;
+        if pending {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    });
+
+    let mut fut = pin!(restartable.on_complete_incremental(|timing| {
+        sessions.lock().unwrap().push(timing);
+    }));
+
+    fut.as_mut().await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    fut.as_mut().await;
+
+    let sessions = sessions.into_inner().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions
+        .iter()
+        .all(|timing| timing.idle < Duration::from_millis(20)));
+}
+
+#[tokio::test]
+async fn poll_fn_idle_between_pending_polls() {
+    let mut polls = 0;
+
+    let output = timed_poll_fn(
+        |cx| {
+            polls += 1;
+
+            if polls < 3 {
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(5));
+                    waker.wake();
+                });
+                Poll::Pending
+            } else {
+                Poll::Ready(42)
+            }
+        },
+        |Timing { idle, busy }| {
+            assert!(idle >= Duration::from_millis(10));
+            assert!(busy < Duration::from_millis(5));
+        },
+    )
+    .await;
+
+    assert_eq!(output, 42);
+    assert_eq!(polls, 3);
+}
+
+#[tokio::test]
+async fn timed_if_idle_bound_skips_busy_futures() {
+    let fired = AtomicUsize::new(0);
+
+    async {
+        spin(Duration::from_millis(5));
+        tokio::task::yield_now().await;
+    }
+    .timed_if_idle_bound(1.0, |_| {
+        fired.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    tokio::time::sleep(Duration::from_millis(5))
+        .timed_if_idle_bound(1.0, |_| {
+            fired.fetch_add(1, Ordering::Relaxed);
+        })
+        .await;
+
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn warn_if_first_poll_ignores_later_polls() {
+    let fired = AtomicUsize::new(0);
+
+    async {
+        tokio::task::yield_now().await;
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    .warn_if_first_poll(Duration::from_millis(5), |_| {
+        fired.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert_eq!(fired.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn shared_instrumented_inside_reports_once() {
+    use futures::future::FutureExt;
+
+    let reported = AtomicUsize::new(0);
+
+    let shared = async {
+        spin(Duration::from_millis(1));
+    }
+    .timed(|Timing { busy, .. }| {
+        assert!(busy >= Duration::from_millis(1));
+        reported.fetch_add(1, Ordering::Relaxed);
+    })
+    .shared();
+
+    futures::join!(shared.clone(), shared.clone(), shared);
+
+    assert_eq!(reported.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn shared_instrumented_outside_attributes_to_poller() {
+    use futures::future::FutureExt;
+
+    let shared = async {
+        spin(Duration::from_millis(1));
+    }
+    .shared();
+
+    let ((), first) = shared.clone().timed_collect().await;
+    let ((), second) = shared.timed_collect().await;
+
+    assert!(first.busy >= Duration::from_millis(1));
+    assert!(second.busy < Duration::from_millis(1));
+}
+
+#[tokio::test]
+async fn timed_select_reports_cancelled_loser() {
+    let loser = async {
+        spin(Duration::from_micros(500));
+        std::future::pending::<()>().await;
+    };
+    let winner = async {
+        tokio::task::yield_now().await;
+        42
+    };
+
+    let output = timed_select(loser, winner, |SelectTiming { winner, loser }| {
+        assert!(loser.busy >= Duration::from_micros(500));
+        assert!(winner.busy < loser.busy);
+    })
+    .await;
+
+    assert_eq!(output, Selected::Right(42));
+}
+
+#[tokio::test]
+async fn timed_lightweight_measures_final_poll() {
+    async {
+        spin(Duration::from_millis(2));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        spin(Duration::from_micros(200));
+    }
+    .timed_lightweight(|elapsed, final_poll_busy| {
+        assert!(elapsed >= Duration::from_millis(7));
+        assert!(final_poll_busy >= Duration::from_micros(200));
+        assert!(final_poll_busy < Duration::from_millis(2));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn timed_thread_local_accumulates_per_thread() {
+    let _ = future_timed::take_thread_local_timing();
+
+    for _ in 0..3 {
+        async {
+            spin(Duration::from_micros(100));
+        }
+        .timed_thread_local()
+        .await;
+    }
+
+    let other = std::thread::spawn(future_timed::take_thread_local_timing)
+        .join()
+        .unwrap();
+    assert!(other.busy.is_zero());
+
+    assert!(future_timed::take_thread_local_timing().busy >= Duration::from_micros(300));
+    assert!(future_timed::take_thread_local_timing().busy.is_zero());
+}
+
+#[tokio::test]
+async fn timed_err_context_maps_only_errors() {
+    let ok = async { Ok::<_, ()>(42) }
+        .timed_err_context(|(), _| unreachable!())
+        .await;
+    assert_eq!(ok, Ok(42));
+
+    let err = async {
+        spin(Duration::from_micros(200));
+        Err::<(), _>("failed")
+    }
+    .timed_err_context(|err, timing| (err, timing.busy))
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.0, "failed");
+    assert!(err.1 >= Duration::from_micros(200));
+}
+
+#[tokio::test]
+async fn timed_precise_idle_attributes_self_wakes_to_scheduling() {
+    async {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    .timed_precise_idle(|Timing { idle, .. }, precise| {
+        assert_eq!(precise.waiting + precise.scheduling_delay, idle);
+        assert!(precise.waiting >= Duration::from_millis(4));
+        assert!(precise.scheduling_delay < Duration::from_millis(4));
+    })
+    .await;
+
+    let mut pending = true;
+
+    std::future::poll_fn(|cx| {
+        if std::mem::take(&mut pending) {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .timed_precise_idle(|_, precise| {
+        assert!(precise.waiting.is_zero());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn idle_definition_excludes_scheduling_delay() {
+    fn woken_once() -> impl std::future::Future<Output = ()> {
+        let mut pending = true;
+        std::future::poll_fn(move |cx| {
+            if std::mem::take(&mut pending) {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+    }
+
+    // Spinning in the last branch of the first round delays polling the woken futures again
+    let delay = async {
+        spin(Duration::from_millis(5));
+    };
+
+    tokio::join!(
+        woken_once().timed_idle_definition(IdleKind::BetweenPolls, |Timing { idle, .. }| {
+            assert!(idle >= Duration::from_millis(5));
+        }),
+        woken_once().timed_idle_definition(IdleKind::Waiting, |Timing { idle, .. }| {
+            assert!(idle.is_zero());
+        }),
+        delay,
+    );
+}
+
+#[tokio::test]
+async fn timed_gated_separates_contention_from_work() {
+    let semaphore = tokio::sync::Semaphore::new(1);
+    let permit = semaphore.acquire().await.unwrap();
+
+    let gated = async {
+        spin(Duration::from_millis(2));
+    }
+    .timed_gated(semaphore.acquire(), |timing| {
+        assert!(timing.wait.idle >= Duration::from_millis(5));
+        assert!(timing.work.busy >= Duration::from_millis(2));
+        assert!(timing.work.idle.is_zero());
+    });
+
+    let release = async {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        drop(permit);
+    };
+
+    tokio::join!(gated, release);
+    assert_eq!(semaphore.available_permits(), 1);
+}
+
+#[tokio::test]
+async fn timed_when_reports_matching_outputs_only() {
+    let reported = AtomicUsize::new(0);
+
+    for n in 0..10 {
+        let output = async move { n }
+            .timed_when(
+                |n| n % 2 == 0,
+                |_| {
+                    reported.fetch_add(1, Ordering::Relaxed);
+                },
+            )
+            .await;
+
+        assert_eq!(output, n);
+    }
+
+    assert_eq!(reported.load(Ordering::Relaxed), 5);
+}
+
+#[tokio::test]
+async fn warn_if_async_completes_warning_before_next_poll() {
+    let warnings = AtomicUsize::new(0);
+    let inner_polls = AtomicUsize::new(0);
+
+    let output = async {
+        for _ in 0..3 {
+            inner_polls.fetch_add(1, Ordering::Relaxed);
+            spin(Duration::from_millis(1));
+            tokio::task::yield_now().await;
+        }
+        inner_polls.fetch_add(1, Ordering::Relaxed);
+        spin(Duration::from_millis(1));
+        42
+    }
+    .warn_if_async(Duration::from_micros(500), |_| {
+        // The inner future must not be polled while a warning is pending
+        let polls = inner_polls.load(Ordering::Relaxed);
+        let (warnings, inner_polls) = (&warnings, &inner_polls);
+        async move {
+            tokio::task::yield_now().await;
+            assert_eq!(inner_polls.load(Ordering::Relaxed), polls);
+            warnings.fetch_add(1, Ordering::Relaxed);
+        }
+    })
+    .await;
+
+    assert_eq!(output, 42);
+    assert_eq!(warnings.load(Ordering::Relaxed), 4);
+}
+
+#[tokio::test]
+async fn timed_idle_hist_counts_each_gap() {
+    let ms = Duration::from_millis;
+    let mut histogram = TimingHistogram::with_buckets(&[ms(1), ms(100)]);
+
+    async {
+        tokio::task::yield_now().await;
+        tokio::time::sleep(ms(5)).await;
+        tokio::task::yield_now().await;
+    }
+    .timed_idle_hist(&mut histogram)
+    .await;
+
+    assert_eq!(
+        histogram.buckets(),
+        &[(ms(1), 2), (ms(100), 1), (Duration::MAX, 0)]
+    );
+}
+
+#[tokio::test]
+async fn timed_marked_sums_sections_and_closes_open_one() {
+    async {
+        timing_mark_end();
+        spin(Duration::from_millis(20));
+
+        timing_mark_start();
+        timing_mark_start();
+        spin(Duration::from_millis(2));
+        tokio::task::yield_now().await;
+        timing_mark_end();
+
+        spin(Duration::from_millis(20));
+
+        timing_mark_start();
+        spin(Duration::from_millis(2));
+    }
+    .timed_marked(|Timing { busy, .. }| {
+        assert!(busy >= Duration::from_millis(4));
+        assert!(busy < Duration::from_millis(20));
+    })
+    .await;
+
+    // Outside of a marked future, marks have no effect
+    timing_mark_start();
+}
+
+#[tokio::test]
+async fn timed_detailed_tracks_polls() {
+    async {
+        spin(Duration::from_micros(100));
+        tokio::task::yield_now().await;
+        spin(Duration::from_millis(1));
+    }
+    .timed_detailed(|detailed| {
+        assert_eq!(detailed.polls, 2);
+        assert!(detailed.first_poll_busy >= Duration::from_micros(100));
+        assert!(detailed.first_poll_busy < detailed.final_poll_busy);
+        assert!(detailed.final_poll_busy >= Duration::from_millis(1));
+        assert_eq!(detailed.max_poll, detailed.final_poll_busy);
+        assert!(detailed.final_poll_ratio() > 0.5);
+        assert_eq!(detailed.timing().busy, detailed.busy);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn timed_detailed_yields_separates_yields_from_waits() {
+    async {
+        tokio::task::yield_now().await;
+        tokio::task::yield_now().await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    .timed_detailed_yields(Duration::from_millis(5), |detailed| {
+        assert_eq!(detailed.polls, 4);
+        assert_eq!(detailed.yields, 2);
+        assert_eq!(detailed.waits, 1);
+    })
+    .await;
+
+    async { tokio::task::yield_now().await }
+        .timed_detailed(|detailed| {
+            assert_eq!(detailed.yields, 0);
+            assert_eq!(detailed.waits, 1);
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn yield_after_yields_between_long_polls() {
+    let polls = AtomicUsize::new(0);
+
+    async {
+        spin(Duration::from_millis(2));
+        tokio::task::yield_now().await;
+        spin(Duration::from_micros(10));
+        tokio::task::yield_now().await;
+    }
+    .yield_after(Duration::from_millis(1))
+    .trace_wakes(|_| {
+        polls.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    // One extra poll for the yield after the first, long poll only
+    assert_eq!(polls.into_inner(), 4);
+}
+
+#[tokio::test]
+async fn timed_adaptive_samples_more_while_slow() {
+    // Slow futures spin well beyond the threshold, so that they are slow even when measuring CPU
+    // time of a preempted thread
+    let state = SamplerState::new(Duration::from_millis(1));
+    let sampled = AtomicUsize::new(0);
+
+    let run = |busy| {
+        async move { spin(busy) }.timed_adaptive(8, &state, |_| {
+            sampled.fetch_add(1, Ordering::Relaxed);
+        })
+    };
+
+    // Only the first of the fast futures is sampled
+    for _ in 0..8 {
+        run(Duration::ZERO).await;
+    }
+
+    assert_eq!(sampled.load(Ordering::Relaxed), 1);
+    assert_eq!(state.interval(8), 8);
+
+    // The ninth future is sampled and slow, so then every fourth is
+    run(Duration::from_millis(4)).await;
+    assert_eq!(sampled.load(Ordering::Relaxed), 2);
+    assert_eq!(state.interval(8), 4);
+
+    for _ in 0..4 {
+        run(Duration::from_millis(4)).await;
+    }
+
+    assert_eq!(sampled.load(Ordering::Relaxed), 3);
+    assert_eq!(state.interval(8), 2);
+
+    // Fast futures back off one step per sample
+    for _ in 0..2 {
+        run(Duration::ZERO).await;
+    }
+
+    assert_eq!(sampled.load(Ordering::Relaxed), 4);
+    assert_eq!(state.interval(8), 3);
+}
+
+#[tokio::test]
+async fn timed_controllable_aborts_on_break() {
+    struct DropFlag<'a>(&'a AtomicUsize);
+
+    impl Drop for DropFlag<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let dropped = AtomicUsize::new(0);
+    let mut calls = 0;
+
+    let result = async {
+        let _flag = DropFlag(&dropped);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    }
+    .timed_controllable(|timing| {
+        calls += 1;
+        if timing.idle >= Duration::from_millis(5) {
+            std::ops::ControlFlow::Break(())
+        } else {
+            std::ops::ControlFlow::Continue(())
+        }
+    })
+    .await;
+
+    let aborted = result.unwrap_err();
+    assert!(aborted.timing().idle >= Duration::from_millis(5));
+    assert!(aborted.to_string().starts_with("future aborted after"));
+    assert_eq!(calls, 2);
+    assert_eq!(dropped.into_inner(), 1);
+
+    // Completed futures are never aborted
+    let result = async { 42 }
+        .timed_controllable(|_| std::ops::ControlFlow::Break(()))
+        .await;
+    assert_eq!(result, Ok(42));
+}
+
+#[tokio::test]
+async fn trace_wakes_reports_each_poll() {
+    let events = std::sync::Mutex::new(Vec::new());
+
+    async {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        spin(Duration::from_millis(1));
+    }
+    .trace_wakes(|event| events.lock().unwrap().push(event))
+    .await;
+
+    let events: Vec<WakeEvent> = events.into_inner().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].poll, 1);
+    assert!(events[0].pending);
+    assert_eq!(events[0].idle, Duration::ZERO);
+    assert_eq!(events[1].poll, 2);
+    assert!(!events[1].pending);
+    assert!(events[1].idle >= Duration::from_millis(4));
+    assert!(events[1].busy >= Duration::from_millis(1));
+}
+
+/// Poll the inner future with a new waker forwarding to the task's waker on every poll.
+struct FreshWaker<Fut>(std::pin::Pin<Box<Fut>>);
+
+struct Forward(std::task::Waker);
+
+impl futures::task::ArcWake for Forward {
+    fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+        arc_self.0.wake_by_ref();
+    }
+}
+
+impl<Fut: std::future::Future> std::future::Future for FreshWaker<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Self::Output> {
+        let waker = futures::task::waker(std::sync::Arc::new(Forward(cx.waker().clone())));
+        self.0
+            .as_mut()
+            .poll(&mut std::task::Context::from_waker(&waker))
+    }
+}
+
+#[tokio::test]
+async fn timed_detailed_counts_waker_changes() {
+    let work = || async {
+        for _ in 0..3 {
+            tokio::task::yield_now().await;
+        }
+    };
+
+    work()
+        .timed_detailed(|detailed| assert_eq!(detailed.waker_changes, 0))
+        .await;
+
+    FreshWaker(Box::pin(work().timed_detailed(|detailed| {
+        assert_eq!(detailed.polls, 4);
+        assert_eq!(detailed.waker_changes, 3);
+    })))
+    .await;
+}
+
+#[tokio::test]
+async fn warn_if_after_warmup_checks_later_polls() {
+    let fired = AtomicUsize::new(0);
+
+    async {
+        std::thread::sleep(Duration::from_millis(10));
+        tokio::task::yield_now().await;
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    .warn_if_after_warmup(Duration::from_millis(5), 1, |duration| {
+        assert!(duration >= Duration::from_millis(10));
+        fired.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn warn_if_after_warmup_reset_restarts_warmup() {
+    let fired = AtomicUsize::new(0);
+
+    let slow_polls = std::future::poll_fn(|cx| {
+        std::thread::sleep(Duration::from_millis(10));
+        cx.waker().wake_by_ref();
+        Poll::<()>::Pending
+    });
+
+    let mut fut = pin!(
+        slow_polls.warn_if_after_warmup(Duration::from_millis(5), 1, |_| {
+            fired.fetch_add(1, Ordering::Relaxed);
+        })
+    );
+
+    assert!(futures::poll!(fut.as_mut()).is_pending());
+    assert!(futures::poll!(fut.as_mut()).is_pending());
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+    fut.as_mut().reset();
+    assert!(futures::poll!(fut.as_mut()).is_pending());
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+    assert!(futures::poll!(fut.as_mut()).is_pending());
+    assert_eq!(fired.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn warn_if_cancelled_ignores_completion() {
+    let fired = AtomicUsize::new(0);
+
+    async {
+        spin(Duration::from_micros(200));
+        tokio::task::yield_now().await;
+    }
+    .warn_if_cancelled(Duration::ZERO, |_| {
+        fired.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert_eq!(fired.load(Ordering::Relaxed), 0);
+
+    let cancelled = async {
+        spin(Duration::from_micros(200));
+        std::future::pending::<()>().await;
+    }
+    .warn_if_cancelled(Duration::from_micros(100), |wasted| {
+        assert!(wasted >= Duration::from_micros(200));
+        fired.fetch_add(1, Ordering::Relaxed);
+    });
+
+    tokio::select! {
+        () = cancelled => unreachable!(),
+        () = tokio::task::yield_now() => {},
+    }
+
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn timed_tree_nests_nodes() {
+    async {
+        async {
+            async {
+                spin(Duration::from_micros(100));
+            }
+            .timed_node("parse")
+            .await;
+            tokio::task::yield_now().await;
+        }
+        .timed_node("read")
+        .await;
+
+        async {}.timed_node("write").await;
+    }
+    .timed_tree("request", |root| {
+        let labels = root
+            .children
+            .iter()
+            .map(|node| node.label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["read", "write"]);
+
+        let read = &root.children[0];
+        assert_eq!(read.children.len(), 1);
+        assert_eq!(read.children[0].label, "parse");
+        assert!(read.timing.busy >= read.children[0].timing.busy);
+        assert!(root.timing.busy >= read.timing.busy);
+    })
+    .await;
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn offload_if_busy_restarts_on_blocking_thread() {
+    use std::sync::Arc;
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let make_attempts = attempts.clone();
+
+    let output = future_timed::offload_if_busy(
+        move || {
+            let attempt = make_attempts.fetch_add(1, Ordering::Relaxed);
+            async move {
+                if attempt == 0 {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                tokio::task::yield_now().await;
+                attempt
+            }
+        },
+        Duration::from_millis(5),
+        tokio::runtime::Handle::current(),
+    )
+    .await;
+
+    assert_eq!(output, 1);
+    assert_eq!(attempts.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn unit_output() {
+    let completed = AtomicUsize::new(0);
+    let complete = || {
+        completed.fetch_add(1, Ordering::Relaxed);
+    };
+
+    let output: () = tokio::task::yield_now()
+        .timed_void(|_| complete())
+        .timed(|_| complete())
+        .elapsed(|_| complete())
+        .lifetime(|_| complete())
+        .timed_vs_wall(|_, _| complete())
+        .warn_if(Duration::ZERO, |_| complete())
+        .timed_if_idle_bound(0.0, |_| complete())
+        .await;
+
+    assert_eq!(output, ());
+    assert!(completed.load(Ordering::Relaxed) >= 7);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn throttle_if_busy_delays_next_poll() {
+    async {
+        spin(Duration::from_millis(2));
+        tokio::task::yield_now().await;
+    }
+    .throttle_if_busy(Duration::from_millis(1), Duration::from_millis(10))
+    .timed(|Timing { idle, .. }| {
+        assert!(idle >= Duration::from_millis(10));
+    })
+    .await;
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn timed_oneshot_reports_dropped_future() {
+    let (fut, rx) = async {
+        spin(Duration::from_micros(200));
+    }
+    .timed_oneshot();
+
+    fut.await;
+    assert!(rx.await.unwrap().busy >= Duration::from_micros(200));
+
+    let (fut, rx) = std::future::pending::<()>().timed_oneshot();
+    drop(fut);
+    assert!(rx.await.is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn timed_timeout_reports_partial_timing() {
+    let reported = AtomicUsize::new(0);
+
+    let result = async {
+        spin(Duration::from_micros(200));
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    }
+    .timed_timeout(Duration::from_millis(5), |Timing { idle, busy }| {
+        assert!(busy >= Duration::from_micros(200));
+        assert!(idle >= Duration::from_millis(4));
+        reported.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert!(result.is_err());
+
+    let result = async { 42 }
+        .timed_timeout(Duration::from_secs(10), |_| {
+            reported.fetch_add(1, Ordering::Relaxed);
+        })
+        .await;
+
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(reported.load(Ordering::Relaxed), 2);
+}
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn timed_unordered_summarizes_completed_futures() {
+    use futures::StreamExt;
+
+    let mut batch = [100, 200, 300]
+        .into_iter()
+        .map(|micros| async move {
+            spin(Duration::from_micros(micros));
+            tokio::task::yield_now().await;
+            micros
+        })
+        .collect::<future_timed::TimedUnordered<_>>();
+
+    assert_eq!(batch.len(), 3);
+    assert!(batch.next().await.is_some());
+    assert_eq!(batch.summary().count, 1);
+
+    while batch.next().await.is_some() {}
+
+    let summary = batch.summary();
+    assert!(batch.is_empty());
+    assert_eq!(summary.count, 3);
+    assert!(summary.busy >= Duration::from_micros(600));
+    assert!(summary.max_busy >= Duration::from_micros(300));
+    assert!(summary.max_busy < summary.busy);
+}
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn timed_join_all_keeps_order() {
+    let branches = [3, 1, 2].map(|millis| async move {
+        spin(Duration::from_millis(millis));
+        tokio::task::yield_now().await;
+        millis
+    });
+
+    let (outputs, timings) = future_timed::timed_join_all(branches).await;
+
+    assert_eq!(outputs, [3, 1, 2]);
+    assert_eq!(timings.len(), 3);
+
+    for (millis, timing) in outputs.into_iter().zip(timings) {
+        assert!(timing.busy >= Duration::from_millis(millis));
+    }
+
+    let (outputs, timings) =
+        future_timed::timed_join_all(Vec::<std::future::Ready<()>>::new()).await;
+    assert!(outputs.is_empty());
+    assert!(timings.is_empty());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn timed_watch_publishes_snapshots() {
+    use futures::StreamExt;
+
+    let (fut, snapshots) = async {
+        spin(Duration::from_micros(200));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    .timed_watch(Duration::from_millis(10));
+
+    let monitor = tokio::spawn(snapshots.collect::<Vec<_>>());
+
+    fut.await;
+    let snapshots = monitor.await.unwrap();
+
+    // Idle time grows with every tick although the sleeping future is not polled
+    assert!(snapshots.len() >= 4);
+    assert!(snapshots.windows(2).all(|w| w[0].idle < w[1].idle));
+    assert!(snapshots.windows(2).all(|w| w[0].busy <= w[1].busy));
+    assert!(snapshots[snapshots.len() - 1].idle >= Duration::from_millis(50));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn timed_watch_only_polls_when_woken() {
+    use futures::StreamExt;
+
+    let polls = AtomicUsize::new(0);
+    let mut sleep = pin!(tokio::time::sleep(Duration::from_millis(50)));
+
+    let (fut, snapshots) = std::future::poll_fn(|cx| {
+        polls.fetch_add(1, Ordering::Relaxed);
+        std::future::Future::poll(sleep.as_mut(), cx)
+    })
+    .timed_watch(Duration::from_millis(10));
+
+    let monitor = tokio::spawn(snapshots.count());
+
+    fut.await;
+
+    assert!(monitor.await.unwrap() >= 4);
+    assert_eq!(polls.load(Ordering::Relaxed), 2);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[should_panic(expected = "period must not be zero")]
+async fn timed_watch_rejects_zero_period() {
+    let _ = async {}.timed_watch(Duration::ZERO);
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn mock_clock_measures_exact_durations() {
+    let clock = future_timed::MockClock::install();
+
+    let output = async {
+        clock.advance(Duration::from_millis(3));
+        42
+    }
+    .warn_if(Duration::from_millis(3), |duration| {
+        assert_eq!(duration, Duration::from_millis(3));
+    })
+    .timed(|Timing { idle, busy }| {
+        assert_eq!(busy, Duration::from_millis(3));
+        assert!(idle.is_zero());
+    })
+    .await;
+
+    assert_eq!(output, 42);
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn assert_idle_under_accepts_idle_equal_to_limit() {
+    let clock = future_timed::MockClock::install();
+
+    let mut fut =
+        std::pin::pin!(tokio::task::yield_now().assert_idle_under(Duration::from_millis(3)));
+
+    assert!(futures::poll!(fut.as_mut()).is_pending());
+    clock.advance(Duration::from_millis(3));
+    fut.await;
+}
+
+#[cfg(feature = "chrome-trace")]
+#[tokio::test]
+async fn timed_chrome_renders_escaped_events() {
+    let trace = future_timed::ChromeTrace::new();
+
+    async {
+        spin(Duration::from_micros(200));
+        tokio::task::yield_now().await;
+    }
+    .timed_chrome("say \"hi\"", &trace)
+    .await;
+
+    let json = trace.to_json();
+    assert_eq!(trace.len(), 2);
+    assert!(json.starts_with(r#"{"traceEvents":[{"name":"say \"hi\"","ph":"X","ts":"#));
+    assert!(json.ends_with("}]}"));
+}
+
+#[cfg(all(feature = "cpu-time", target_os = "linux"))]
+#[tokio::test]
+async fn warn_if_cpu_ignores_blocked_polls() {
+    let warnings = AtomicUsize::new(0);
+
+    async {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    .warn_if_cpu(Duration::from_millis(10), |_| {
+        warnings.fetch_add(1, Ordering::Relaxed);
+    })
+    .await;
+
+    assert_eq!(warnings.load(Ordering::Relaxed), 0);
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn regression_check_against_saved_baseline() {
+    use future_timed::TimingBaseline;
+
+    // Record a reference run and save it, e.g. to a file checked into the repository
+    let reference = async { spin(Duration::from_millis(2)) }
+        .timed_collect()
+        .await
+        .1;
+    let saved = serde_json::to_string(&TimingBaseline::new(reference)).unwrap();
+    assert!(saved.contains("\"busy_ns\":") && saved.contains("\"idle_ns\":"));
+
+    // Later, load it and check a slower run against it
+    let baseline: TimingBaseline = serde_json::from_str(&saved).unwrap();
+    assert_eq!(baseline.busy, reference.busy);
+
+    let regressions = AtomicUsize::new(0);
+
+    async { spin(Duration::from_millis(10)) }
+        .timed_regression_check(&baseline, 0.5, |_| {
+            regressions.fetch_add(1, Ordering::Relaxed);
+        })
+        .await;
+
+    assert_eq!(regressions.load(Ordering::Relaxed), 1);
+}
+
+#[cfg(feature = "statsd")]
+#[tokio::test]
+async fn timed_statsd_sends_busy_time_with_tags() {
+    use cadence::{SpyMetricSink, StatsdClient};
+
+    let (rx, sink) = SpyMetricSink::new();
+    let client = StatsdClient::builder("app", sink)
+        .with_tag("env", "test")
+        .build();
+
+    async { spin(Duration::from_millis(2)) }
+        .timed_statsd(&client, "plain")
+        .await;
+
+    async { spin(Duration::from_millis(2)) }
+        .timed_statsd_tagged(&client, "tagged", &[("route", "/users"), ("method", "GET")])
+        .await;
+
+    let plain = String::from_utf8(rx.try_recv().unwrap()).unwrap();
+    assert!(plain.starts_with("app.plain:"));
+    assert!(plain.ends_with("|ms|#env:test"));
+    assert_ne!(plain, "app.plain:0|ms|#env:test");
+
+    let tagged = String::from_utf8(rx.try_recv().unwrap()).unwrap();
+    assert!(tagged.starts_with("app.tagged:"));
+    assert!(tagged.contains("|ms|#"));
+    assert!(tagged.contains("route:/users,method:GET"));
+    assert!(tagged.contains("env:test"));
+}
+
+#[cfg(feature = "opentelemetry")]
+#[tokio::test]
+async fn timed_otel_records_on_current_span() {
+    use opentelemetry::trace::{FutureExt, Span, SpanContext, Status, TraceContextExt};
+    use opentelemetry::{Context, KeyValue, Value};
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    /// Span recording attributes and event names.
+    #[derive(Debug)]
+    struct Recording(SpanContext, Arc<Mutex<(Vec<KeyValue>, Vec<String>)>>);
+
+    impl Span for Recording {
+        fn add_event_with_timestamp<T>(&mut self, name: T, _: SystemTime, _: Vec<KeyValue>)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+            self.1.lock().unwrap().1.push(name.into().into_owned());
+        }
+
+        fn span_context(&self) -> &SpanContext {
+            &self.0
+        }
+
+        fn is_recording(&self) -> bool {
+            true
+        }
+
+        fn set_attribute(&mut self, attribute: KeyValue) {
+            self.1.lock().unwrap().0.push(attribute);
+        }
+
+        fn set_status(&mut self, _: Status) {}
+
+        fn update_name<T>(&mut self, _: T)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+
+        fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+
+        fn end_with_timestamp(&mut self, _: SystemTime) {}
+    }
+
+    let recorded = Arc::new(Mutex::new((Vec::new(), Vec::new())));
+    let span = Recording(SpanContext::empty_context(), recorded.clone());
+    let cx = Context::current_with_span(span);
+
+    async {
+        spin(Duration::from_millis(2));
+        tokio::task::yield_now().await;
+    }
+    .timed_otel("fetch")
+    .with_context(cx)
+    .await;
+
+    let (attributes, events) = recorded.lock().unwrap().clone();
+    assert_eq!(events, ["fetch"]);
+    assert_eq!(attributes.len(), 2);
+    assert_eq!(attributes[0].key.as_str(), "fetch.busy_ns");
+    assert_eq!(attributes[1].key.as_str(), "fetch.idle_ns");
+    assert!(matches!(attributes[0].value, Value::I64(busy) if busy >= 2_000_000));
+
+    // Without a current span, nothing is recorded and nothing fails
+    assert_eq!(async { 42 }.timed_otel("fetch").await, 42);
+}